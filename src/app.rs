@@ -1,7 +1,22 @@
 use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
 
-use crate::command::{Command, CommandOption};
+use colored::*;
+
+use crate::command::{
+    match_prefix, process_value, split_option_token, Builtin, Command, CommandOption,
+    OptionRelation, OptionTokenError,
+};
+use crate::completion::{self, Shell};
+use crate::config::Config;
+use crate::error::ParseError;
 use crate::help::*;
+use crate::invocation::Invocation;
+use crate::manpage;
+use crate::markdown;
+use crate::suggest::suggest;
 use crate::types::*;
 
 /// Holds information about the application: commands, options, name, version, etc.
@@ -10,7 +25,12 @@ pub struct App {
     pub(crate) desc: String,
     pub(crate) version: String,
     pub(crate) commands: Vec<Command>,
+    pub(crate) builtins: Vec<Command>,
     pub(crate) options: Vec<CommandOption>,
+    pub(crate) global_options: Vec<CommandOption>,
+    pub(crate) config_file: Option<PathBuf>,
+    pub(crate) xdg_config: bool,
+    pub(crate) infer_prefixes: bool,
 }
 
 /// Macro to create an app and initialize it with the current crate name, description, and version.
@@ -50,12 +70,34 @@ impl App {
             CommandOption::new("version", "Print version").alias("v"),
         ];
 
+        // Hidden commands that are built into every application
+        let mut completions = Command::new(
+            "completions",
+            "Generate a shell completion script",
+            |_, _| Ok(None),
+        )
+        .arg_typed::<Shell>("shell");
+        completions.builtin = Some(Builtin::Completions);
+
+        let mut complete = Command::new(
+            "__complete",
+            "Print the values that complete a command line",
+            |_, _| Ok(None),
+        )
+        .variadic_arg("words", 0, None);
+        complete.builtin = Some(Builtin::Complete);
+
         App {
             name: String::new(),
             desc: String::new(),
             version: String::new(),
             commands: vec![],
+            builtins: vec![completions, complete],
             options,
+            global_options: vec![],
+            config_file: None,
+            xdg_config: false,
+            infer_prefixes: false,
         }
     }
 
@@ -164,6 +206,147 @@ impl App {
         self
     }
 
+    /// Add a global option to the application
+    ///
+    /// Global options are accepted by every command and subcommand of the
+    /// application, in addition to their own options. They can be passed in
+    /// before or after the command, e.g. `app -C dir deploy` or
+    /// `app deploy -C dir`. They are passed into
+    /// the command function along with the options of the command, and are
+    /// listed under `GLOBAL OPTIONS` in every help menu. If a command has an
+    /// option with the same alias, the option of the command is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `option` - The option to accept in every command
+    ///
+    /// # Panics
+    ///
+    /// Panics if the option has one of the aliases of the help or version
+    /// options: `--help`, `-h`, `--version` or `-v`.
+    ///
+    /// # Examples
+    ///
+    /// Creating an application where every command accepts `--verbose`:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!()
+    ///     .global_option(CommandOption::new("verbose", "Print more output").alias("V"));
+    /// ```
+    pub fn global_option(mut self, option: CommandOption) -> Self {
+        // The help and version options would be matched instead of the global
+        // option before the command, but not after it
+        let builtin_aliases: Vec<&str> = self.options.iter().flat_map(|o| o.aliases()).collect();
+        if let Some(alias) = option
+            .aliases()
+            .into_iter()
+            .find(|alias| builtin_aliases.contains(alias))
+        {
+            panic!(
+                "Global options can't use the aliases of the help and version options: `{}`",
+                alias
+            );
+        }
+
+        self.global_options.push(option);
+        self
+    }
+
+    /// Load option defaults from a configuration file.
+    ///
+    /// Options that are not passed in on the command line or through their
+    /// [environment variable](`CommandOption::env()`) are read from the file
+    /// before falling back to their [default value](`CommandOption::default_value()`).
+    /// The file is only read once such an option is found, and never for the
+    /// help menu or the builtin commands, so an invalid file doesn't prevent
+    /// showing help or completing the command line. If the file doesn't
+    /// exist, it is ignored.
+    ///
+    /// The file uses a simple TOML-like format. Sections are named after the
+    /// long alias of a command, with subcommands separated by dots. Keys are the
+    /// long aliases of options without the leading dashes. Keys that come before
+    /// the first section apply to every command. Flag options are set with
+    /// `true` or `false`.
+    ///
+    /// ```text
+    /// # Applies to every command
+    /// verbose = true
+    ///
+    /// [deploy]
+    /// region = "eu-west-1"
+    ///
+    /// [remote.add]
+    /// fetch = true
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path of the configuration file
+    ///
+    /// # Examples
+    ///
+    /// Creating an app that reads its configuration from `/etc/my_app.toml`:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().config_file("/etc/my_app.toml");
+    /// ```
+    pub fn config_file(mut self, path: &str) -> Self {
+        self.config_file = Some(PathBuf::from(path));
+        self
+    }
+
+    /// Load option defaults from a configuration file at the XDG path.
+    ///
+    /// Behaves the same as [config_file](`App::config_file()`), but the file is
+    /// located at `$XDG_CONFIG_HOME/<app_name>/config.toml`, or at
+    /// `$HOME/.config/<app_name>/config.toml` if `XDG_CONFIG_HOME` is not set.
+    /// A path set with [config_file](`App::config_file()`) takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// Creating an app that reads its configuration from the XDG path:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().xdg_config_file();
+    /// ```
+    pub fn xdg_config_file(mut self) -> Self {
+        self.xdg_config = true;
+        self
+    }
+
+    /// Accept unambiguous prefixes of commands and long options.
+    ///
+    /// When enabled, a command, subcommand, or long option can be shortened
+    /// to any prefix of its long alias that no other command or option shares.
+    /// For example, `app stat --verb` runs `app status --verbose`. A prefix that
+    /// matches more than one command or option is an error that lists the
+    /// candidates. Exact aliases always take precedence over prefixes.
+    /// Subcommands of a command that takes arguments can't be shortened, since
+    /// the prefix could be the value of an argument.
+    ///
+    /// Prefix matching is disabled by default, because adding a command or
+    /// option can make prefixes that used to work ambiguous.
+    ///
+    /// # Examples
+    ///
+    /// Creating an app that accepts prefixes:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().infer_prefixes();
+    /// ```
+    pub fn infer_prefixes(mut self) -> Self {
+        self.infer_prefixes = true;
+        self
+    }
+
     /// Runs the application with command line arguments
     ///
     /// Collects the arguments from the command line, parses them, and passes them into
@@ -172,7 +355,8 @@ impl App {
     ///
     /// The result of running the command is returned by this function. If any errors
     /// occur when parsing the command line input (command not found, wrong option name, etc.),
-    /// then this function will print a help menu and return `Ok(None)`.
+    /// then this function will print a help menu and return `Ok(None)`. Use
+    /// [try_run_custom](`App::try_run_custom()`) to get the parsing error instead.
     ///
     /// # Examples
     ///
@@ -198,107 +382,501 @@ impl App {
     /// # Arguments
     /// * `args` - A vector of strings representing the arguments to be parsed
     pub fn run_custom(&self, args: Vec<String>) -> Result<Option<String>, String> {
-        // Print help if there are no arguments
-        if args.len() <= 1 {
-            print_help_app(self, None);
-            return Ok(None);
+        match self.try_run_custom(args) {
+            Ok(result) => result,
+            Err(e) => {
+                print!("{}", format_parse_error(self, &e));
+                Ok(None)
+            }
+        }
+    }
+
+    /// Runs the application with command line arguments and exits the process.
+    ///
+    /// Behaves like [run](`App::run()`), but handles the output and exit code
+    /// of the process so that `main` doesn't have to:
+    ///
+    /// * `Ok(Some(output))` - `output` is printed to stdout and the process exits with `0`
+    /// * `Ok(None)` - The process exits with `0`
+    /// * `Err(error)` - `error` is printed to stderr and the process exits with `1`
+    /// * Help or version requested - The help menu or version is printed to stdout and
+    ///   the process exits with `0`
+    /// * Parsing error - The help menu and error are printed to stderr and the process
+    ///   exits with `2`, the conventional exit code for usage errors
+    /// * Invalid [configuration file](`App::config_file()`) - Only the error is printed
+    ///   to stderr and the process exits with `78`, the conventional exit code for
+    ///   configuration errors
+    ///
+    /// # Examples
+    ///
+    /// Creating an app and running it as the whole `main` function:
+    ///
+    /// ```no_run
+    /// use climb::*;
+    ///
+    /// fn main() {
+    ///     create_app!().run_and_exit();
+    /// }
+    /// ```
+    pub fn run_and_exit(&self) -> ! {
+        process::exit(self.run_for_exit_code(env::args().collect()))
+    }
+
+    // Used internally by the run and exit function to run the application,
+    // print its output, and return the exit code of the process
+    pub(crate) fn run_for_exit_code(&self, args: Vec<String>) -> i32 {
+        match self.try_run_custom(args) {
+            Ok(Ok(Some(output))) => {
+                println!("{}", output);
+                0
+            }
+            Ok(Ok(None)) => 0,
+            Ok(Err(e)) => {
+                eprintln!("{}", e.red());
+                1
+            }
+            Err(e @ (ParseError::HelpRequested { .. } | ParseError::VersionRequested)) => {
+                print!("{}", format_parse_error(self, &e));
+                0
+            }
+            Err(e @ ParseError::InvalidConfig { .. }) => {
+                eprint!("{}", format_parse_error(self, &e));
+                78
+            }
+            Err(e) => {
+                eprint!("{}", format_parse_error(self, &e));
+                2
+            }
         }
+    }
+
+    /// Runs the application with custom arguments, returning parsing errors.
+    ///
+    /// Behaves like [run_custom](`App::run_custom()`), but nothing is printed
+    /// when the arguments can't be parsed. Instead, a [ParseError] describing
+    /// the problem is returned so that the application can react to it. Help
+    /// and version requests are also returned as errors.
+    ///
+    /// If the arguments are parsed successfully, the result of running the
+    /// command is returned inside `Ok`.
+    ///
+    /// # Arguments
+    /// * `args` - A vector of strings representing the arguments to be parsed
+    ///
+    /// # Examples
+    ///
+    /// Running an app with a command that doesn't exist:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().name("app");
+    ///
+    /// let result = my_app.try_run_custom(vec![
+    ///     String::from("app"),
+    ///     String::from("missing"),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     result.unwrap_err(),
+    ///     ParseError::UnknownCommand {
+    ///         token: String::from("missing"),
+    ///         command: None,
+    ///         suggestions: vec![],
+    ///     }
+    /// );
+    /// ```
+    pub fn try_run_custom(&self, args: Vec<String>) -> Result<FunctionResult, ParseError> {
+        let invocation = self.try_parse(args)?;
+        Ok(self.dispatch(invocation))
+    }
+
+    /// Parses the arguments without running anything.
+    ///
+    /// Finds the command named by the arguments and parses its arguments
+    /// and options, returning an [Invocation] that holds them. Nothing is
+    /// printed and no command function is called. Pass the invocation to
+    /// [dispatch](`App::dispatch()`) to run the command.
+    ///
+    /// If the arguments can't be parsed, or help or version information is
+    /// requested, a [ParseError] is returned instead.
+    ///
+    /// # Arguments
+    /// * `args` - A vector of strings representing the arguments to be parsed
+    ///
+    /// # Examples
+    ///
+    /// Parsing the arguments and logging the command before running it:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_app = create_app!()
+    ///     .name("app")
+    ///     .command(Command::new("cmd_name", "cmd_desc", example_cmd_fn));
+    ///
+    /// let args = vec![String::from("app"), String::from("cmd_name")];
+    ///
+    /// if let Ok(invocation) = my_app.try_parse(args) {
+    ///     println!("running {}", invocation.command_name());
+    ///     let _ = my_app.dispatch(invocation);
+    /// }
+    /// ```
+    pub fn try_parse(&self, args: Vec<String>) -> Result<Invocation<'_>, ParseError> {
+        // Options before the command are either application options (help
+        // and version) or global options. Global options are parsed along
+        // with the options of the command, once it is found
+        let app_options: Vec<&CommandOption> =
+            self.options.iter().chain(&self.global_options).collect();
+        let mut leading = Vec::<String>::new();
+        let mut it = args.iter().skip(1).peekable();
+        while let Some(arg) = it.next_if(|arg| arg.starts_with('-') && *arg != "-") {
+            leading.push(arg.clone());
+
+            let split = match self.parse_option_token(&app_options, None, arg) {
+                Ok(split) => split,
+                // Reported along with the command, once it is known
+                Err(ParseError::UnexpectedOptionArgument { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+
+            for (option, _) in &split {
+                match option.alias_long.as_str() {
+                    "--help" => return Err(ParseError::HelpRequested { command: None }),
+                    "--version" => return Err(ParseError::VersionRequested),
+                    _ => {}
+                }
+            }
 
-        // If the first argument is an option, then we are not running a command
-        // Check if the option is help or version
-        let first_arg = args.get(1).unwrap();
-        if first_arg.starts_with('-') {
-            match first_arg.as_str() {
-                "-h" | "--help" => print_help_app(self, None),
-                "-v" | "--version" => print_version(self),
-                x => print_help_app(
-                    self,
-                    Some(format!("The given option does not exist: `{}`", x)),
-                ),
+            // The value is either attached to the token (`--long=value` or
+            // `-svalue`) or is the next argument
+            if let Some((option, None)) = split.last() {
+                if option.arg.is_some() {
+                    leading.extend(it.next().cloned());
+                }
             }
-            return Ok(None);
         }
 
-        // Otherwise, the first argument is a command
-        // Get the command if it exists, else print a help screen
-        let alias = args.get(1).unwrap();
-        let Some(command) = self.lookup_command(alias) else {
-            print_help_app(
-                self,
-                Some(format!("The given command does not exist: `{}`", alias)),
-            );
-            return Ok(None);
+        // There must be a command after the options
+        let Some(first_arg) = it.next() else {
+            return Err(ParseError::MissingCommand);
         };
 
-        // Parse the arguments and pass them into the command to be executed
-        let (input, options) = match self.parse_args(command, args) {
-            Ok(Some((i, o))) => (i, o),
-            Ok(None) => return Ok(None),
-            Err(e) => {
-                print_help_command(self, command, Some(e));
-                return Ok(None);
+        // Otherwise, the first argument is a command
+        // Get the command if it exists
+        let command = match self.lookup_command(first_arg) {
+            Ok(Some(command)) => command,
+            Ok(None) => {
+                return Err(ParseError::UnknownCommand {
+                    token: first_arg.clone(),
+                    command: None,
+                    suggestions: suggest(
+                        first_arg,
+                        self.commands.iter().flat_map(Command::aliases),
+                    ),
+                })
+            }
+            Err(candidates) => {
+                return Err(ParseError::AmbiguousCommand {
+                    token: first_arg.clone(),
+                    command: None,
+                    candidates,
+                })
             }
         };
 
-        // Run the command function
-        let function = command.function;
-        function(input, options)
+        // Walk down the subcommands for as long as the arguments name one.
+        // A command that has subcommands and takes no arguments can only be
+        // followed by one of its subcommands or by options
+        let mut command_path = vec![command];
+        while let Some(alias) = it.peek() {
+            let parent = command_path.last().unwrap();
+            let subcommand = match parent.lookup_subcommand(alias, self.infer_prefixes) {
+                Ok(Some(subcommand)) => subcommand,
+                Ok(None) if parent.expects_subcommand() && !alias.starts_with('-') => {
+                    return Err(ParseError::UnknownCommand {
+                        token: alias.to_string(),
+                        command: Some(command_path_aliases(&command_path)),
+                        suggestions: suggest(
+                            alias,
+                            parent.subcommands.iter().flat_map(Command::aliases),
+                        ),
+                    })
+                }
+                Ok(None) => break,
+                Err(candidates) => {
+                    return Err(ParseError::AmbiguousCommand {
+                        token: alias.to_string(),
+                        command: Some(command_path_aliases(&command_path)),
+                        candidates,
+                    })
+                }
+            };
+
+            command_path.push(subcommand);
+            it.next();
+        }
+
+        // Parse the arguments of the command
+        let args: Vec<String> = it.cloned().collect();
+        let (input, options) = self.parse_args(&command_path, &leading, &args)?;
+
+        Ok(Invocation {
+            command_path,
+            input,
+            options,
+        })
+    }
+
+    /// Runs the command of a parsed [Invocation].
+    ///
+    /// Passes the arguments and options of the invocation into the command
+    /// function and returns its result. See [try_parse](`App::try_parse()`).
+    ///
+    /// # Arguments
+    /// * `invocation` - The invocation returned by [try_parse](`App::try_parse()`)
+    pub fn dispatch(&self, invocation: Invocation) -> FunctionResult {
+        let command = invocation.command();
+
+        match command.builtin {
+            Some(Builtin::Completions) => {
+                let shell: Shell = invocation.input.value(0).unwrap();
+                let mut script = Vec::<u8>::new();
+
+                self.generate_completions(shell, &mut script)
+                    .map_err(|e| e.to_string())?;
+
+                let script = String::from_utf8(script).map_err(|e| e.to_string())?;
+                Ok(Some(script.trim_end().to_string()))
+            }
+            Some(Builtin::Complete) => {
+                let values = completion::complete(self, &invocation.input);
+                Ok((!values.is_empty()).then(|| values.join("\n")))
+            }
+            None => (command.function)(invocation.input, invocation.options),
+        }
+    }
+
+    /// Generates a shell completion script for the application.
+    ///
+    /// The script completes the commands, subcommands, and options of the
+    /// application, using both their long and short aliases.
+    ///
+    /// The values of arguments and options are completed at runtime: the
+    /// script calls the hidden `__complete` command of the application with
+    /// the partial command line, which prints the values described by the
+    /// [hint](`crate::ValueHint`) of the argument or option that is being completed.
+    /// File names are completed after options that take an argument and have
+    /// no hint.
+    ///
+    /// Every application also has a hidden `completions <SHELL>` command that
+    /// prints the script, so users can install completions with, for example:
+    ///
+    /// ```text
+    /// $ my_app completions bash > /etc/bash_completion.d/my_app
+    /// $ my_app completions zsh > "${fpath[1]}/_my_app"
+    /// $ my_app completions fish > ~/.config/fish/completions/my_app.fish
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `shell` - The shell to generate the script for
+    /// * `out` - Where to write the script
+    ///
+    /// # Examples
+    ///
+    /// Writing a bash completion script to stdout:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().name("my_app");
+    ///
+    /// my_app
+    ///     .generate_completions(Shell::Bash, &mut std::io::stdout())
+    ///     .unwrap();
+    /// ```
+    pub fn generate_completions(&self, shell: Shell, out: &mut impl Write) -> io::Result<()> {
+        completion::generate(self, shell, out)
+    }
+
+    /// Renders a section 1 man page for the application.
+    ///
+    /// The page is written in roff and holds the NAME, SYNOPSIS, and
+    /// DESCRIPTION of the application, its OPTIONS, and a COMMANDS section
+    /// with the usage, arguments, and options of every command and
+    /// subcommand.
+    ///
+    /// # Examples
+    ///
+    /// Writing the man page of an application to a file:
+    ///
+    /// ```no_run
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().name("my_app");
+    ///
+    /// std::fs::write("my_app.1", my_app.render_manpage()).unwrap();
+    /// ```
+    pub fn render_manpage(&self) -> String {
+        manpage::render_app(self)
+    }
+
+    /// Renders Markdown reference documentation for the application.
+    ///
+    /// The document walks the application the same way the help menus do:
+    /// the usage and options of the application, followed by a section for
+    /// every command and subcommand with its usage, aliases, and tables of
+    /// its arguments, options, and subcommands.
+    ///
+    /// # Examples
+    ///
+    /// Writing the reference documentation of an application to a file:
+    ///
+    /// ```no_run
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().name("my_app");
+    ///
+    /// std::fs::write("REFERENCE.md", my_app.render_markdown()).unwrap();
+    /// ```
+    pub fn render_markdown(&self) -> String {
+        markdown::render(self)
+    }
+
+    /// Renders a section 1 man page for every command and subcommand.
+    ///
+    /// Returns the file name and contents of each page. Pages are named
+    /// after the application and the long aliases of the command, e.g.
+    /// `my_app-remote-add.1`, and refer back to the man page of the
+    /// application returned by [render_manpage](`App::render_manpage()`).
+    ///
+    /// # Examples
+    ///
+    /// Writing the man pages of every command to a folder:
+    ///
+    /// ```no_run
+    /// use climb::*;
+    ///
+    /// let my_app = create_app!().name("my_app");
+    ///
+    /// for (file_name, page) in my_app.render_command_manpages() {
+    ///     std::fs::write(format!("man/{}", file_name), page).unwrap();
+    /// }
+    /// ```
+    pub fn render_command_manpages(&self) -> Vec<(String, String)> {
+        collect_command_paths(self)
+            .iter()
+            .map(|command_path| {
+                (
+                    format!("{}.1", manpage::page_name(self, command_path)),
+                    manpage::render_command(self, command_path),
+                )
+            })
+            .collect()
     }
 
-    // Used internally by the run function to parse the arguments
+    // Used internally by the run function to parse the arguments that follow
+    // the command path, and the global options that come before the command
     fn parse_args(
         &self,
-        command: &Command,
-        args: Vec<String>,
-    ) -> Result<Option<(FunctionInput, FunctionOptions)>, String> {
+        command_path: &[&Command],
+        leading: &[String],
+        args: &[String],
+    ) -> Result<(FunctionInput, FunctionOptions), ParseError> {
+        let command = *command_path.last().unwrap();
+        let command_aliases = command_path_aliases(command_path);
         let mut inputs = Vec::<String>::new();
         let mut options = Vec::<FunctionOption>::new();
 
-        // Create an iterator over the arguments and skip the first two elements
-        // (exe name, function name)
-        let mut it = args.iter().skip(2);
-
-        // Parse the arguments
-        while let Some(arg) = it.next() {
-            // `arg` is an option
-            if arg.starts_with('-') {
-                // Check if the option is `-h` or `--help`
-                match arg.as_str() {
-                    "-h" | "--help" => {
-                        print_help_command(self, command, None);
-                        return Ok(None);
-                    }
-                    _ => (),
-                }
+        // Options before the command can only be global options
+        let global_options: Vec<&CommandOption> = self.global_options.iter().collect();
+        let command_options: Vec<&CommandOption> =
+            command.options.iter().chain(&self.global_options).collect();
 
-                // Check if the given option exists for the function
-                let Some(option) = command.has_option(arg) else {
-                    return Err(format!("Given option does not exist: `{}`", arg));
+        // Parse the arguments, along with the options they can hold
+        let mut it = leading
+            .iter()
+            .map(|arg| (arg, &global_options))
+            .chain(args.iter().map(|arg| (arg, &command_options)));
+        while let Some((arg, scope)) = it.next() {
+            // Everything after `--` is a command argument. The values fill the
+            // arguments of the command and count towards their amount
+            if arg == "--" {
+                inputs.extend(it.by_ref().map(|(arg, _)| arg.clone()));
+                break;
+            }
+
+            // `arg` holds one or more options. A lone `-` and negative numbers
+            // are command arguments
+            if arg.starts_with('-') && arg != "-" && !command.is_negative_number(arg) {
+                let split = match self.parse_option_token(scope, Some(&command_aliases), arg) {
+                    Ok(split) => split,
+                    // Unknown options are command arguments if the command
+                    // allows it. Tokens that start with a known option, such as
+                    // a flag with a value or a partly known cluster, are errors
+                    Err(ParseError::UnknownOption { token, .. })
+                        if command.allow_hyphen_values && arg.starts_with(&token) =>
+                    {
+                        inputs.push(arg.clone());
+                        continue;
+                    }
+                    Err(e) => return Err(e),
                 };
 
-                // If the option takes an argument, get it and continue
-                if let Some(option_name) = &option.arg {
-                    let Some(next_arg) = it.next() else {
-                        return Err(format!(
-                            "{} not provided for option: `{}`",
-                            option_name, arg
-                        ));
-                    };
+                for (option, attached_value) in split {
+                    // Check if the option is `-h` or `--help`
+                    if option.alias_long == "--help" {
+                        return Err(ParseError::HelpRequested {
+                            command: Some(command_aliases),
+                        });
+                    }
 
-                    // If the argument is another option, return error
-                    if next_arg.starts_with('-') {
-                        return Err(format!(
-                            "{} not provided for option: `{}`",
-                            option_name, arg
-                        ));
+                    // Only repeatable options can be passed in more than once
+                    if !option.repeatable && options.count(&option.alias_long) > 0 {
+                        return Err(ParseError::DuplicateOption {
+                            token: option.alias_long.clone(),
+                            command: command_aliases,
+                        });
                     }
 
-                    options.push(FunctionOption(
-                        option.alias_long.clone(),
-                        Some(next_arg.clone()),
-                    ));
-                } else {
-                    options.push(FunctionOption(option.alias_long.clone(), None::<String>));
+                    // If the option takes an argument, get it and continue
+                    let Some(option_name) = &option.arg else {
+                        options.push(FunctionOption(option.alias_long.clone(), None::<String>));
+                        continue;
+                    };
+
+                    let value = match attached_value {
+                        Some(value) => value,
+                        None => {
+                            let missing_argument = || ParseError::MissingOptionArgument {
+                                token: arg.clone(),
+                                command: command_aliases.clone(),
+                                argument: option_name.clone(),
+                            };
+
+                            let Some((next_arg, _)) = it.next() else {
+                                return Err(missing_argument());
+                            };
+
+                            // If the argument is another option, return error
+                            let is_value =
+                                option.allow_hyphen_values || command.is_negative_number(next_arg);
+                            if next_arg.starts_with('-') && !is_value {
+                                return Err(missing_argument());
+                            }
+
+                            next_arg.clone()
+                        }
+                    };
+
+                    let value = Self::check_option_value(option, &value, None, &command_aliases)?;
+                    options.push(FunctionOption(option.alias_long.clone(), Some(value)));
                 }
             } else {
                 // `arg` is a command argument
@@ -307,41 +885,321 @@ impl App {
             }
         }
 
-        if inputs.len() != command.args.len() {
-            return Err(format!(
-                "Incorrect amount of arguments provided for command: {}",
-                &command.alias_long
-            ));
+        // Builtin commands are not configured by the application, so none of
+        // its options are filled in or checked for them
+        let (checked_options, relations) = match command.builtin {
+            Some(_) => (vec![], &[][..]),
+            None => (command_options.clone(), &command.relations[..]),
+        };
+
+        // Fill in the options that were not provided, first from the
+        // environment, then from the configuration file, and then from their
+        // default values. The configuration file is only read once a value is
+        // missing, and never for the help option or builtin commands. Options
+        // that conflict with an option passed in on the command line are not
+        // filled in, since the command line takes precedence
+        let passed: Vec<String> = options.iter().map(|option| option.0.clone()).collect();
+        let mut config = None::<Config>;
+        for option in checked_options.iter().copied() {
+            if options.count(&option.alias_long) > 0
+                || option.alias_long == "--help"
+                || Self::conflicts_with(relations, &option.alias_long, &passed)
+            {
+                continue;
+            }
+
+            let mut value = option.env.as_ref().and_then(|var| env::var(var).ok());
+            let env_var = option.env.as_ref().filter(|_| value.is_some());
+            if value.is_none() {
+                if config.is_none() {
+                    config = Some(self.load_config()?);
+                }
+
+                value = config.as_ref().and_then(|config| {
+                    config.get(&command_aliases.join(" "), &option.alias_long[2..])
+                });
+            }
+
+            let Some(value) = value.or_else(|| option.default.clone()) else {
+                continue;
+            };
+
+            if option.arg.is_some() {
+                let value = Self::check_option_value(option, &value, env_var, &command_aliases)?;
+                options.push(FunctionOption(option.alias_long.clone(), Some(value)));
+            } else if is_truthy(&value) {
+                options.push(FunctionOption(option.alias_long.clone(), None::<String>));
+            }
+        }
+
+        // Check that all of the required options were provided
+        for option in checked_options.iter().filter(|option| option.required) {
+            if options.count(&option.alias_long) == 0 {
+                return Err(ParseError::MissingRequiredOption {
+                    token: option.alias_long.clone(),
+                    command: command_aliases,
+                });
+            }
+        }
+
+        // Check the relationships between the options, including the ones
+        // that were filled in
+        for relation in relations {
+            Self::check_option_relation(relation, &options, &command_aliases)?;
         }
-        if options.len() > command.options.len() {
-            return Err(format!(
-                "Too many options provided for command: {}",
-                &command.alias_long
-            ));
+
+        // Fill in the trailing arguments that were not provided, first from
+        // the environment and then from their default values. A variadic
+        // argument is filled with a single value, and only if it received none
+        let provided = inputs.len();
+        let mut from_env = Vec::<usize>::new();
+        for arg in command.args.iter().skip(inputs.len()) {
+            let env_value = arg.env.as_ref().and_then(|var| env::var(var).ok());
+            if env_value.is_some() {
+                from_env.push(inputs.len());
+            }
+
+            match env_value.or_else(|| arg.default.clone()) {
+                Some(value) => inputs.push(value),
+                None => break,
+            }
+        }
+
+        let too_many = command.max_args().is_some_and(|max| inputs.len() > max);
+        if inputs.len() < command.min_args() || too_many {
+            return Err(ParseError::WrongArgumentCount {
+                command: command_aliases,
+                min: command.min_args(),
+                max: command.max_args(),
+                actual: provided,
+            });
         }
 
-        Ok(Some((inputs, options)))
+        // Normalize the arguments, and check that they are one of their
+        // choices, can be parsed, and pass their validators. This includes
+        // the values filled in from the environment and defaults
+        for (index, input) in inputs.iter_mut().enumerate() {
+            let Some(arg) = command.arg_at(index) else {
+                continue;
+            };
+
+            let processed = process_value(
+                input,
+                &arg.transforms,
+                &arg.choices,
+                &arg.parser,
+                &arg.validators,
+            );
+
+            match processed {
+                Ok(value) => *input = value,
+                Err(e) => {
+                    let env_var = arg.env.as_ref().filter(|_| from_env.contains(&index));
+                    return Err(ParseError::InvalidValue {
+                        token: input.clone(),
+                        command: command_aliases,
+                        argument: format!("<{}>", arg.name),
+                        message: with_env_source(e, env_var),
+                    });
+                }
+            }
+        }
+
+        Ok((inputs, options))
     }
 
-    // Used internally by the run function to return the corresponding command
-    // given its short or long alias
-    fn lookup_command(&self, alias: &String) -> Option<&Command> {
-        for command in &self.commands {
-            let equals_alias_short = if let Some(alias_short) = &command.alias_short {
-                *alias_short == *alias
-            } else {
-                false
+    // Used internally by the parse function to read the configuration file,
+    // if the application has one
+    fn load_config(&self) -> Result<Config, ParseError> {
+        if let Some(path) = &self.config_file {
+            return Config::load(path);
+        }
+
+        if !self.xdg_config {
+            return Ok(Config::default());
+        }
+
+        let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => match env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(".config"),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        Config::load(&config_dir.join(&self.name).join("config.toml"))
+    }
+
+    // Used internally by the parse function to normalize the value of an
+    // option, and check that it is one of its choices, can be parsed, and
+    // passes its validators. `env_var` is the environment variable the value
+    // was read from, if any. Returns the normalized value
+    fn check_option_value(
+        option: &CommandOption,
+        value: &str,
+        env_var: Option<&String>,
+        command_aliases: &[String],
+    ) -> Result<String, ParseError> {
+        let processed = process_value(
+            value,
+            &option.transforms,
+            &option.choices,
+            &option.parser,
+            &option.validators,
+        );
+
+        processed.map_err(|e| ParseError::InvalidValue {
+            token: value.to_string(),
+            command: command_aliases.to_vec(),
+            argument: format!("`{}`", option.alias_long),
+            message: with_env_source(e, env_var),
+        })
+    }
+
+    // Used internally by the parse function to check if an option conflicts
+    // with one of the options that were passed in on the command line
+    fn conflicts_with(relations: &[OptionRelation], alias: &str, passed: &[String]) -> bool {
+        relations.iter().any(|relation| match relation {
+            OptionRelation::Conflicts(aliases) => {
+                aliases.iter().any(|other| other == alias)
+                    && aliases.iter().any(|other| passed.contains(other))
+            }
+            _ => false,
+        })
+    }
+
+    // Used internally by the parse function to check that the options that
+    // were passed in follow a relationship declared on the command
+    fn check_option_relation(
+        relation: &OptionRelation,
+        options: &FunctionOptions,
+        command_aliases: &[String],
+    ) -> Result<(), ParseError> {
+        match relation {
+            OptionRelation::Conflicts(aliases) => {
+                let passed: Vec<&String> = aliases
+                    .iter()
+                    .filter(|alias| options.count(alias) > 0)
+                    .collect();
+
+                if let [first, second, ..] = passed.as_slice() {
+                    return Err(ParseError::ConflictingOptions {
+                        token: first.to_string(),
+                        other: second.to_string(),
+                        command: command_aliases.to_vec(),
+                    });
+                }
+            }
+            OptionRelation::Requires(alias, required) => {
+                if options.count(alias) > 0 && options.count(required) == 0 {
+                    return Err(ParseError::MissingOptionDependency {
+                        token: alias.clone(),
+                        required: required.clone(),
+                        command: command_aliases.to_vec(),
+                    });
+                }
+            }
+            OptionRelation::OneOf(aliases) => {
+                if aliases.iter().all(|alias| options.count(alias) == 0) {
+                    return Err(ParseError::MissingOneOfOptions {
+                        tokens: aliases.clone(),
+                        command: command_aliases.to_vec(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Used internally by the parse function to split an option token into the
+    // options it holds, out of the given options. `command` is `None` for the
+    // options that come before the command
+    fn parse_option_token<'a>(
+        &self,
+        options: &[&'a CommandOption],
+        command: Option<&[String]>,
+        token: &str,
+    ) -> Result<Vec<(&'a CommandOption, Option<String>)>, ParseError> {
+        split_option_token(options, token, self.infer_prefixes).map_err(|e| match e {
+            OptionTokenError::Unknown(alias) => ParseError::UnknownOption {
+                suggestions: suggest(&alias, options.iter().flat_map(|option| option.aliases())),
+                token: alias,
+                command: command.map(<[String]>::to_vec),
+            },
+            OptionTokenError::Ambiguous(alias, candidates) => ParseError::AmbiguousOption {
+                token: alias,
+                command: command.map(<[String]>::to_vec),
+                candidates,
+            },
+            OptionTokenError::UnexpectedValue(alias) => ParseError::UnexpectedOptionArgument {
+                token: alias,
+                command: command.unwrap_or_default().to_vec(),
+            },
+        })
+    }
+
+    // Used internally to find the commands named by a path of long aliases,
+    // as stored in `ParseError`
+    pub(crate) fn lookup_command_path(&self, path: &[String]) -> Vec<&Command> {
+        let mut command_path = Vec::<&Command>::new();
+
+        for alias in path {
+            let command = match command_path.last() {
+                Some(parent) => parent
+                    .subcommands
+                    .iter()
+                    .find(|command| command.alias_long == *alias),
+                None => self
+                    .commands
+                    .iter()
+                    .chain(&self.builtins)
+                    .find(|command| command.alias_long == *alias),
             };
 
-            if *command.alias_long == *alias || equals_alias_short {
-                return Some(command);
+            match command {
+                Some(command) => command_path.push(command),
+                None => break,
             }
         }
 
-        None
+        command_path
+    }
+
+    // Used internally by the run function to return the corresponding command
+    // given its short or long alias. Builtin commands can't be abbreviated.
+    // Returns the long aliases of the matching commands if a prefix is ambiguous
+    pub(crate) fn lookup_command(&self, alias: &String) -> Result<Option<&Command>, Vec<String>> {
+        if let Some(command) = self
+            .commands
+            .iter()
+            .chain(&self.builtins)
+            .find(|command| command.has_alias(alias))
+        {
+            return Ok(Some(command));
+        }
+
+        match self.infer_prefixes {
+            true => match_prefix(alias, &self.commands, |command| &command.alias_long),
+            false => Ok(None),
+        }
     }
 }
 
+// Adds the environment variable that an invalid value was read from to the
+// message of the error, since it doesn't appear on the command line
+fn with_env_source(message: String, env_var: Option<&String>) -> String {
+    match env_var {
+        Some(var) => format!("{} (read from the `{}` environment variable)", message, var),
+        None => message,
+    }
+}
+
+// Returns true if an environment variable value turns a flag option on
+fn is_truthy(value: &str) -> bool {
+    matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
 impl Default for App {
     fn default() -> Self {
         App::new()
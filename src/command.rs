@@ -1,5 +1,383 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::completion::ValueHint;
 use crate::types::*;
 
+// Checks a value passed in from the command line, returning an error message
+// if it is invalid
+pub(crate) type ValueCheck = Box<dyn Fn(&str) -> Result<(), String>>;
+
+// Wraps a value parser so that it can be stored without its value type
+fn value_check<P>(parser: P) -> ValueCheck
+where
+    P: ValueParser + 'static,
+{
+    Box::new(move |value| parser.parse(value).map(|_| ()))
+}
+
+// Normalizes a value passed in from the command line
+pub(crate) type ValueTransform = Box<dyn Fn(&str) -> String>;
+
+// Normalizes a value with the transforms of an argument or option, and then
+// checks that it is one of the choices, can be parsed, and passes the
+// validators. Returns the normalized value, or an error message if it is invalid
+pub(crate) fn process_value(
+    value: &str,
+    transforms: &[ValueTransform],
+    choices: &[String],
+    parser: &Option<ValueCheck>,
+    validators: &[ValueCheck],
+) -> Result<String, String> {
+    let value = transforms
+        .iter()
+        .fold(value.to_string(), |value, transform| transform(&value));
+
+    // Any value is accepted if there are no choices
+    if !choices.is_empty() && !choices.contains(&value) {
+        return Err(format!("expected one of {}", choices.join(", ")));
+    }
+
+    if let Some(parser) = parser {
+        parser(&value)?;
+    }
+
+    for validator in validators {
+        validator(&value)?;
+    }
+
+    Ok(value)
+}
+
+/// Holds information about command arguments.
+///
+/// `CommandArg`s are the positional arguments that are passed in when calling
+/// application commands from the command line. Most arguments only need a
+/// name and can be added to commands with [Command::arg] using a string
+/// slice. Construct a `CommandArg` to configure the argument further, and
+/// add it with [Command::arg]. The other argument builders of [Command] are
+/// shortcuts for the builders of `CommandArg`.
+///
+/// # Examples
+///
+/// Creating an argument that is read from an environment variable when it
+/// is not passed in:
+///
+/// ```
+/// use climb::*;
+///
+/// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+///     println!("my example function");
+///     Ok(None)
+/// }
+///
+/// let my_command = Command::new(
+///     "login",
+///     "Log into the server",
+///     example_cmd_fn
+/// )
+/// .arg("user")
+/// .arg(CommandArg::new("token").env("APP_TOKEN"));
+/// ```
+pub struct CommandArg {
+    pub(crate) name: String,
+    pub(crate) parser: Option<ValueCheck>,
+    pub(crate) default: Option<String>,
+    pub(crate) env: Option<String>,
+    pub(crate) hint: Option<ValueHint>,
+    pub(crate) choices: Vec<String>,
+    pub(crate) validators: Vec<ValueCheck>,
+    pub(crate) transforms: Vec<ValueTransform>,
+    // The minimum and maximum amount of values the argument accepts. The
+    // maximum is `None` if there is no limit
+    pub(crate) min: usize,
+    pub(crate) max: Option<usize>,
+}
+
+impl CommandArg {
+    /// Construct and return a required argument with the given name.
+    ///
+    /// # Arguments
+    /// * `name` - String slice that holds the name of the argument
+    pub fn new(name: &str) -> Self {
+        CommandArg {
+            name: name.to_uppercase(),
+            parser: None,
+            default: None,
+            env: None,
+            hint: None,
+            choices: vec![],
+            validators: vec![],
+            transforms: vec![],
+            min: 1,
+            max: Some(1),
+        }
+    }
+
+    /// Read the argument from an environment variable when it is not passed in.
+    ///
+    /// Arguments are positional, so only trailing arguments can be left out
+    /// and read from the environment. A value passed in on the command line
+    /// always takes precedence over the environment variable, which takes
+    /// precedence over the default value of an optional argument. The name
+    /// of the variable is shown in the command help menu.
+    ///
+    /// # Arguments
+    /// * `var` - String slice that holds the name of the environment variable
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that can be set with the `APP_TOKEN` environment variable:
+    ///
+    /// ```
+    /// use climb::CommandArg;
+    ///
+    /// let my_arg = CommandArg::new("token").env("APP_TOKEN");
+    /// ```
+    pub fn env(mut self, var: &str) -> Self {
+        self.env = Some(String::from(var));
+        self
+    }
+
+    /// Make the argument optional.
+    ///
+    /// Optional arguments can be left out when calling the command. If they
+    /// are, the default value is passed into the command function instead.
+    /// Optional arguments must be added to a command after all of its
+    /// required arguments. See [Command::optional_arg].
+    ///
+    /// # Arguments
+    /// * `default` - String slice that holds the value used when the argument
+    ///   is not provided
+    ///
+    /// # Examples
+    ///
+    /// Creating an optional argument that can also be set with an environment variable:
+    ///
+    /// ```
+    /// use climb::CommandArg;
+    ///
+    /// let my_arg = CommandArg::new("dest").optional(".").env("APP_DEST");
+    /// ```
+    pub fn optional(mut self, default: &str) -> Self {
+        self.default = Some(String::from(default));
+        self.min = 0;
+        self
+    }
+
+    /// Make the argument accept any number of values between `min` and `max`.
+    ///
+    /// A variadic argument must be the last argument of a command. If it is
+    /// read from an environment variable, the value of the variable is passed
+    /// in as a single value. See [Command::variadic_arg].
+    ///
+    /// # Arguments
+    /// * `min` - The minimum amount of values that must be provided
+    /// * `max` - The maximum amount of values that can be provided, or `None`
+    ///   for no limit
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that takes one or more files:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_arg = CommandArg::new("files").variadic(1, None).hint(ValueHint::File);
+    /// ```
+    pub fn variadic(mut self, min: usize, max: Option<usize>) -> Self {
+        if max.is_some_and(|max| max < min || max == 0) {
+            panic!(
+                "Variadic arguments need a maximum of at least one and at least the minimum: `{}`",
+                self.name
+            );
+        }
+
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Parse the values of the argument as the type `T`.
+    ///
+    /// If a value can't be parsed, an error is shown and the command function
+    /// is not called. See [Command::arg_typed].
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that takes a port number:
+    ///
+    /// ```
+    /// use climb::CommandArg;
+    ///
+    /// let my_arg = CommandArg::new("port").typed::<u16>().env("APP_PORT");
+    /// ```
+    pub fn typed<T>(self) -> Self
+    where
+        T: FromStr + 'static,
+        T::Err: Display,
+    {
+        self.parser(FromStrParser::<T>::new())
+    }
+
+    /// Check the values of the argument with a [ValueParser].
+    ///
+    /// Behaves the same as [typed](`CommandArg::typed()`), but uses a custom
+    /// parser instead of the [FromStr] implementation of a type.
+    ///
+    /// # Arguments
+    /// * `parser` - The parser used to check the argument
+    pub fn parser<P>(mut self, parser: P) -> Self
+    where
+        P: ValueParser + 'static,
+    {
+        self.parser = Some(value_check(parser));
+        self
+    }
+
+    /// Describe how the values of the argument are completed.
+    ///
+    /// The hint is used by the [completion scripts](`crate::App::generate_completions()`)
+    /// of the application. Arguments without a hint complete their choices,
+    /// or nothing if they have none (file names in fish).
+    ///
+    /// # Arguments
+    /// * `hint` - How the values of the argument are completed
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that completes folder names:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_arg = CommandArg::new("dest").hint(ValueHint::Directory);
+    /// ```
+    pub fn hint(mut self, hint: ValueHint) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Restrict the argument to a set of values.
+    ///
+    /// Values outside of the set are rejected with an error that lists the
+    /// valid values. The values are shown in the command help menu and are
+    /// completed by the [completion scripts](`crate::App::generate_completions()`)
+    /// of the application. Choices can be set on optional and variadic
+    /// arguments too.
+    ///
+    /// Values read from the [environment](`CommandArg::env()`) are checked
+    /// as well, and the error names the variable they were read from.
+    ///
+    /// # Arguments
+    /// * `values` - The values that the argument accepts
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that accepts one of three environments:
+    ///
+    /// ```
+    /// use climb::CommandArg;
+    ///
+    /// let my_arg = CommandArg::new("env").choices(["dev", "staging", "production"]);
+    /// ```
+    pub fn choices<'a>(mut self, values: impl IntoIterator<Item = &'a str>) -> Self {
+        self.choices = values.into_iter().map(String::from).collect();
+        self
+    }
+
+    /// Add a validator that checks the values of the argument.
+    ///
+    /// The validator returns an error message if a value is invalid, which is
+    /// shown along with the command help menu. The command function is not
+    /// called. Validators run after the [transforms](`CommandArg::transform()`),
+    /// the choices, and the type of the argument have been checked, in the
+    /// order they were added.
+    ///
+    /// Values read from the environment and default values are validated
+    /// too, so an invalid default is only reported when the command is called.
+    /// Each value of a [variadic](`CommandArg::variadic()`) argument is
+    /// validated on its own.
+    ///
+    /// # Arguments
+    /// * `validator` - Closure that checks a value of the argument
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that only accepts paths that exist:
+    ///
+    /// ```
+    /// use climb::CommandArg;
+    /// use std::path::Path;
+    ///
+    /// let my_arg = CommandArg::new("path").validator(|value| {
+    ///     match Path::new(value).exists() {
+    ///         true => Ok(()),
+    ///         false => Err(String::from("the path does not exist")),
+    ///     }
+    /// });
+    /// ```
+    pub fn validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Add a transform that normalizes the values of the argument.
+    ///
+    /// Transforms run before the values are checked, in the order they were
+    /// added. Values read from the environment and default values are
+    /// transformed too, as is each value of a variadic argument. The command
+    /// function receives the normalized values.
+    ///
+    /// # Arguments
+    /// * `transform` - Closure that returns the normalized value
+    ///
+    /// # Examples
+    ///
+    /// Creating an argument that ignores surrounding whitespace and case:
+    ///
+    /// ```
+    /// use climb::CommandArg;
+    ///
+    /// let my_arg = CommandArg::new("name")
+    ///     .transform(|value| value.trim().to_string())
+    ///     .transform(|value| value.to_lowercase());
+    /// ```
+    pub fn transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(&str) -> String + 'static,
+    {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    // Returns true if the argument accepts more than a single value
+    pub(crate) fn is_variadic(&self) -> bool {
+        self.max != Some(1)
+    }
+}
+
+impl From<&str> for CommandArg {
+    fn from(name: &str) -> Self {
+        CommandArg::new(name)
+    }
+}
+
+impl From<&String> for CommandArg {
+    fn from(name: &String) -> Self {
+        CommandArg::new(name)
+    }
+}
+
+impl From<String> for CommandArg {
+    fn from(name: String) -> Self {
+        CommandArg::new(&name)
+    }
+}
+
 ///  Holds information about command options.
 ///
 /// `CommandOption`s are the options that can be passed in when calling
@@ -34,6 +412,16 @@ pub struct CommandOption {
     pub(crate) alias_long: String,
     pub(crate) alias_short: Option<String>,
     pub(crate) arg: Option<String>,
+    pub(crate) parser: Option<ValueCheck>,
+    pub(crate) repeatable: bool,
+    pub(crate) required: bool,
+    pub(crate) allow_hyphen_values: bool,
+    pub(crate) env: Option<String>,
+    pub(crate) default: Option<String>,
+    pub(crate) hint: Option<ValueHint>,
+    pub(crate) choices: Vec<String>,
+    pub(crate) validators: Vec<ValueCheck>,
+    pub(crate) transforms: Vec<ValueTransform>,
     pub(crate) description: String,
 }
 
@@ -76,6 +464,16 @@ impl CommandOption {
             alias_long: alias,
             alias_short: None,
             arg: None,
+            parser: None,
+            repeatable: false,
+            required: false,
+            allow_hyphen_values: false,
+            env: None,
+            default: None,
+            hint: None,
+            choices: vec![],
+            validators: vec![],
+            transforms: vec![],
             description: String::from(desc),
         }
     }
@@ -104,6 +502,7 @@ impl CommandOption {
     /// )
     /// .alias("r");
     /// ```
+    #[allow(clippy::doc_lazy_continuation)]
     pub fn alias(mut self, alias: &str) -> Self {
         if alias.len() != 1 {
             panic!(
@@ -146,106 +545,536 @@ impl CommandOption {
         self.arg = Some(argument_name.to_uppercase());
         self
     }
-}
-
-/// Holds information about the commands the application can call.
-///
-/// A command stores an alias to call it by, valid options, arguments, and
-/// a function that is called whenever the command is executed. This function
-/// is the code that you should write to perform the logic of the command.
-///
-/// When you call the command from the command line, Climb will parse the arguments
-/// and pass them into your function. See more about this in the [CommandFunction]
-/// documentation.
-///
-/// # Examples
-///
-/// Creating a command, changing some of its values, and adding it to
-/// an application:
-///
-/// ```
-/// use climb::*;
-///
-/// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
-///     println!("my example function");
-///     Ok(None)
-/// }
-///
-/// let my_command = Command::new(
-///     "cmd_name",
-///     "cmd_desc",
-///     example_cmd_fn
-/// )
-/// .alias("c")
-/// .option(CommandOption::new(
-///     "option_name",
-///     "option_desc"
-/// ))
-/// .arg("arg1")
-/// .arg("arg2");
-///
-/// let my_app = create_app!()
-///     .command(my_command);
-/// ```
-pub struct Command {
-    pub(crate) function: CommandFunction,
-    pub(crate) alias_long: String,
-    pub(crate) alias_short: Option<String>,
-    pub(crate) options: Vec<CommandOption>,
-    pub(crate) args: Vec<String>,
-    pub(crate) desc: String,
-}
 
-impl Command {
-    /// Construct and return a command with the given alias, desc,
-    /// and function.
+    /// Allow the option to be passed in more than once
     ///
-    /// # Arguments
-    /// * `alias` - String slice that holds the alias used to call the
-    /// command from the terminal
-    /// * `desc` - String slice that holds the command description
-    /// * `function` - Function that matches the [CommandFunction] signature
+    /// By default, passing in the same option more than once is an error.
+    /// Every occurrence of a repeatable option is passed into the command
+    /// function, in the order they were given. Use
+    /// [OptionValues::count] to count them, e.g. for `-vvv` style verbosity,
+    /// or [OptionValues::values] to collect their arguments.
     ///
     /// # Examples
     ///
-    /// Construct a new command:
+    /// Creating an option that can be passed in multiple times:
     ///
     /// ```
-    /// use climb::*;
-    ///
-    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
-    ///     println!("my example function");
-    ///     Ok(None)
-    /// }
+    /// use climb::CommandOption;
     ///
-    /// let my_command = Command::new(
-    ///     "cmd_name",
-    ///     "cmd_desc",
-    ///     example_cmd_fn
-    /// );
+    /// let my_option = CommandOption::new(
+    ///     "include",
+    ///     "Include a folder in the search"
+    /// )
+    /// .arg("folder")
+    /// .repeatable();
     /// ```
-    pub fn new(alias: &str, desc: &str, function: CommandFunction) -> Self {
-        if alias.len() <= 1 {
-            panic!(
-                "Long command aliases should have more than a single character: `{}`",
-                alias
-            );
-        }
-
-        // Construct a default command with only the help menu option
-        Command {
-            function,
-            alias_long: alias.to_lowercase(),
-            alias_short: None,
-            options: vec![CommandOption::new("help", "Print help information").alias("h")],
-            args: vec![],
-            desc: String::from(desc),
-        }
+    ///
+    /// This option can be used like this:
+    ///
+    /// `[COMMAND] --include <FOLDER> --include <FOLDER> ...`
+    pub fn repeatable(mut self) -> Self {
+        self.repeatable = true;
+        self
     }
 
-    /// Assign an short alias to the command (single character).
+    /// Require the option to be passed in
     ///
-    /// The command can be called using either the normal long alias or this
+    /// Options are optional by default. If a required option is missing
+    /// when the command is called, an error is shown and the command function
+    /// is not called. Required options are shown in the usage line of the
+    /// command help menu.
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that must always be passed in:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "region",
+    ///     "The region to deploy to"
+    /// )
+    /// .arg("region")
+    /// .required();
+    /// ```
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Allow the argument of the option to start with a dash
+    ///
+    /// By default, an option argument that starts with a dash is treated as
+    /// another option, unless it is a negative number. With this setting,
+    /// the token after the option is always used as its argument.
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that takes arguments like `-O2`:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "compiler-flag",
+    ///     "Pass a flag to the compiler"
+    /// )
+    /// .arg("flag")
+    /// .allow_hyphen_values();
+    /// ```
+    ///
+    /// This option can be used like this:
+    ///
+    /// `[COMMAND] --compiler-flag -O2 ...`
+    pub fn allow_hyphen_values(mut self) -> Self {
+        self.allow_hyphen_values = true;
+        self
+    }
+
+    /// Read the option from an environment variable when it is not passed in
+    ///
+    /// If the option takes an argument, the value of the variable is used as
+    /// the argument. Otherwise, the option is set if the variable holds `1`,
+    /// `true`, `yes`, or `on`. An option passed in on the command line always
+    /// takes precedence over the environment variable. The name of the
+    /// variable is shown in the command help menu.
+    ///
+    /// # Arguments
+    /// * `var` - String slice that holds the name of the environment variable
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that can be set with the `APP_TOKEN` environment variable:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "token",
+    ///     "The token used to log in"
+    /// )
+    /// .arg("token")
+    /// .env("APP_TOKEN");
+    /// ```
+    pub fn env(mut self, var: &str) -> Self {
+        self.env = Some(String::from(var));
+        self
+    }
+
+    /// Assign a default value to the option
+    ///
+    /// The default value is used when the option is not passed in on the
+    /// command line, through its [environment variable](`CommandOption::env()`),
+    /// or through the [configuration file](`crate::App::config_file()`) of the
+    /// application. If the option takes an argument, the default value is used
+    /// as the argument. Otherwise, the option is set if the default value is
+    /// `true`. The default value is shown in the command help menu.
+    ///
+    /// # Arguments
+    /// * `value` - String slice that holds the default value
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that defaults to `us-east-1`:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "region",
+    ///     "The region to deploy to"
+    /// )
+    /// .arg("region")
+    /// .default_value("us-east-1");
+    /// ```
+    pub fn default_value(mut self, value: &str) -> Self {
+        self.default = Some(String::from(value));
+        self
+    }
+
+    /// Restrict the argument of the option to a set of values.
+    ///
+    /// Values outside of the set are rejected with an error that lists the
+    /// valid values. The values are shown in the command help menu and are
+    /// completed by the [completion scripts](`crate::App::generate_completions()`)
+    /// of the application.
+    ///
+    /// Values read from the [environment](`CommandOption::env()`) are checked
+    /// as well, and the error names the variable they were read from.
+    ///
+    /// # Arguments
+    /// * `values` - The values that the argument of the option accepts
+    ///
+    /// # Panics
+    ///
+    /// Panics if the option doesn't take an argument. Call
+    /// [arg](`CommandOption::arg()`) first.
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that accepts one of three log levels:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "level",
+    ///     "The log level"
+    /// )
+    /// .arg("level")
+    /// .choices(["debug", "info", "warn"]);
+    /// ```
+    pub fn choices<'a>(mut self, values: impl IntoIterator<Item = &'a str>) -> Self {
+        if self.arg.is_none() {
+            panic!(
+                "Choices can only be set on options that take an argument: `{}`",
+                self.alias_long
+            );
+        }
+
+        self.choices = values.into_iter().map(String::from).collect();
+        self
+    }
+
+    /// Add a validator that checks the argument of the option.
+    ///
+    /// The validator returns an error message if the argument is invalid,
+    /// which is shown along with the command help menu. The command function
+    /// is not called. Validators run after the
+    /// [transforms](`CommandOption::transform()`), the choices, and the type
+    /// of the argument have been checked, in the order they were added.
+    ///
+    /// Arguments read from the environment, the configuration file, or the
+    /// default value are validated too, so an invalid default is only
+    /// reported when the command is called.
+    ///
+    /// # Arguments
+    /// * `validator` - Closure that checks the argument of the option
+    ///
+    /// # Panics
+    ///
+    /// Panics if the option doesn't take an argument. Call
+    /// [arg](`CommandOption::arg()`) first.
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that only accepts ports in a range:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "port",
+    ///     "The port to listen on"
+    /// )
+    /// .arg_typed::<u16>("port")
+    /// .validator(|value| match value.parse::<u16>() {
+    ///     Ok(1024..) => Ok(()),
+    ///     _ => Err(String::from("expected a port of at least 1024")),
+    /// });
+    /// ```
+    pub fn validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        if self.arg.is_none() {
+            panic!(
+                "Validators can only be added to options that take an argument: `{}`",
+                self.alias_long
+            );
+        }
+
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Add a transform that normalizes the argument of the option.
+    ///
+    /// Transforms run before the argument is checked, in the order they were
+    /// added. Arguments read from the environment, the configuration file, or
+    /// the default value are transformed too. The command function receives
+    /// the normalized argument.
+    ///
+    /// # Arguments
+    /// * `transform` - Closure that returns the normalized argument
+    ///
+    /// # Panics
+    ///
+    /// Panics if the option doesn't take an argument. Call
+    /// [arg](`CommandOption::arg()`) first.
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that resolves its argument to an absolute path:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    /// use std::fs;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "output",
+    ///     "The output folder"
+    /// )
+    /// .arg("folder")
+    /// .transform(|value| match fs::canonicalize(value) {
+    ///     Ok(path) => path.display().to_string(),
+    ///     Err(_) => value.to_string(),
+    /// });
+    /// ```
+    pub fn transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(&str) -> String + 'static,
+    {
+        if self.arg.is_none() {
+            panic!(
+                "Transforms can only be added to options that take an argument: `{}`",
+                self.alias_long
+            );
+        }
+
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Describe how the argument of the option is completed.
+    ///
+    /// The hint is used by the [completion scripts](`crate::App::generate_completions()`)
+    /// of the application. Options that take an argument complete file names
+    /// if they have no hint.
+    ///
+    /// # Arguments
+    /// * `hint` - How the argument of the option is completed
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that completes one of three formats:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "format",
+    ///     "The format of the output"
+    /// )
+    /// .arg("format")
+    /// .hint(ValueHint::Choices(vec![
+    ///     String::from("json"),
+    ///     String::from("yaml"),
+    ///     String::from("toml"),
+    /// ]));
+    /// ```
+    pub fn hint(mut self, hint: ValueHint) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Assign a typed argument to the option
+    ///
+    /// Behaves the same as [arg](`CommandOption::arg()`), but the argument
+    /// is parsed as the type `T` when the command line is parsed. If the
+    /// argument can't be parsed, an error is shown and the command function
+    /// is not called.
+    ///
+    /// # Arguments:
+    /// * `argument_name` - The name of the argument
+    ///
+    /// # Examples
+    ///
+    /// Creating an option that takes a number:
+    ///
+    /// ```
+    /// use climb::CommandOption;
+    ///
+    /// let my_option = CommandOption::new(
+    ///     "depth",
+    ///     "How many folders deep to search"
+    /// )
+    /// .arg_typed::<u32>("depth");
+    /// ```
+    ///
+    /// The argument can then be retrieved in the command function using
+    /// `options.value::<u32>("--depth")`. See [OptionValues].
+    pub fn arg_typed<T>(self, argument_name: &str) -> Self
+    where
+        T: FromStr + 'static,
+        T::Err: Display,
+    {
+        self.arg_parser(argument_name, FromStrParser::<T>::new())
+    }
+
+    /// Assign an argument to the option that is checked with a [ValueParser]
+    ///
+    /// Behaves the same as [arg_typed](`CommandOption::arg_typed()`), but
+    /// uses a custom parser instead of the [FromStr] implementation of a type.
+    ///
+    /// # Arguments:
+    /// * `argument_name` - The name of the argument
+    /// * `parser` - The parser used to check the argument
+    pub fn arg_parser<P>(mut self, argument_name: &str, parser: P) -> Self
+    where
+        P: ValueParser + 'static,
+    {
+        self.arg = Some(argument_name.to_uppercase());
+        self.parser = Some(value_check(parser));
+        self
+    }
+
+    // Returns the long and short aliases of the option
+    pub(crate) fn aliases(&self) -> Vec<&str> {
+        let mut aliases = vec![self.alias_long.as_str()];
+        aliases.extend(self.alias_short.as_deref());
+        aliases
+    }
+}
+
+/// Holds information about the commands the application can call.
+///
+/// A command stores an alias to call it by, valid options, arguments, and
+/// a function that is called whenever the command is executed. This function
+/// is the code that you should write to perform the logic of the command.
+///
+/// When you call the command from the command line, Climb will parse the arguments
+/// and pass them into your function. See more about this in the [CommandFunction]
+/// documentation.
+///
+/// # Examples
+///
+/// Creating a command, changing some of its values, and adding it to
+/// an application:
+///
+/// ```
+/// use climb::*;
+///
+/// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+///     println!("my example function");
+///     Ok(None)
+/// }
+///
+/// let my_command = Command::new(
+///     "cmd_name",
+///     "cmd_desc",
+///     example_cmd_fn
+/// )
+/// .alias("c")
+/// .option(CommandOption::new(
+///     "option_name",
+///     "option_desc"
+/// ))
+/// .arg("arg1")
+/// .arg("arg2");
+///
+/// let my_app = create_app!()
+///     .command(my_command);
+/// ```
+pub struct Command {
+    pub(crate) function: CommandFunction,
+    pub(crate) alias_long: String,
+    pub(crate) alias_short: Option<String>,
+    pub(crate) options: Vec<CommandOption>,
+    pub(crate) args: Vec<CommandArg>,
+    pub(crate) desc: String,
+    pub(crate) subcommands: Vec<Command>,
+    pub(crate) allow_hyphen_values: bool,
+    pub(crate) relations: Vec<OptionRelation>,
+    pub(crate) builtin: Option<Builtin>,
+}
+
+// Relationships between the options of a command, checked after the options
+// are parsed and filled in. Options are stored by their long alias, including
+// the dashes
+pub(crate) enum OptionRelation {
+    // At most one of the options can be passed in
+    Conflicts(Vec<String>),
+    // If the first option is passed in, the second one must be too
+    Requires(String, String),
+    // At least one of the options must be passed in
+    OneOf(Vec<String>),
+}
+
+// Commands that are built into every application. Their behavior is
+// implemented by the application instead of a command function
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Builtin {
+    Completions,
+    Complete,
+}
+
+impl Command {
+    /// Construct and return a command with the given alias, desc,
+    /// and function.
+    ///
+    /// # Arguments
+    /// * `alias` - String slice that holds the alias used to call the
+    /// command from the terminal
+    /// * `desc` - String slice that holds the command description
+    /// * `function` - Function or closure that matches the [CommandFunction] signature
+    ///
+    /// # Examples
+    ///
+    /// Construct a new command:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "cmd_name",
+    ///     "cmd_desc",
+    ///     example_cmd_fn
+    /// );
+    /// ```
+    ///
+    /// Construct a new command from a closure that captures some state:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// let greeting = String::from("Hello");
+    ///
+    /// let my_command = Command::new(
+    ///     "greet",
+    ///     "Greet someone",
+    ///     move |input: FunctionInput, _: FunctionOptions| {
+    ///         Ok(Some(format!("{} {}", greeting, input[0])))
+    ///     }
+    /// )
+    /// .arg("name");
+    /// ```
+    #[allow(clippy::doc_lazy_continuation)]
+    pub fn new<F>(alias: &str, desc: &str, function: F) -> Self
+    where
+        F: Fn(FunctionInput, FunctionOptions) -> FunctionResult + 'static,
+    {
+        if alias.len() <= 1 {
+            panic!(
+                "Long command aliases should have more than a single character: `{}`",
+                alias
+            );
+        }
+
+        // Construct a default command with only the help menu option
+        Command {
+            function: Box::new(function),
+            alias_long: alias.to_lowercase(),
+            alias_short: None,
+            options: vec![CommandOption::new("help", "Print help information").alias("h")],
+            args: vec![],
+            desc: String::from(desc),
+            subcommands: vec![],
+            allow_hyphen_values: false,
+            relations: vec![],
+            builtin: None,
+        }
+    }
+
+    /// Assign an short alias to the command (single character).
+    ///
+    /// The command can be called using either the normal long alias or this
     /// shorter one.
     ///
     /// # Arguments
@@ -273,6 +1102,7 @@ impl Command {
     /// ```
     ///
     /// The command can now be called using either `cmd_name` or `c`.
+    #[allow(clippy::doc_lazy_continuation)]
     pub fn alias(mut self, alias: &str) -> Self {
         if alias.len() != 1 {
             panic!(
@@ -329,7 +1159,8 @@ impl Command {
     /// a file, you could pass in the file name as an argument.
     ///
     /// # Arguments
-    /// * `name` - String slice that holds the name of the argument
+    /// * `arg` - String that holds the name of the argument, or a [CommandArg]
+    ///   struct
     ///
     /// # Examples
     ///
@@ -354,25 +1185,609 @@ impl Command {
     /// The command can be called using:
     ///
     /// `[app_name] cmd_name <arg1> ...`
-    pub fn arg(mut self, name: &str) -> Self {
-        self.args.push(name.to_uppercase());
+    pub fn arg(self, arg: impl Into<CommandArg>) -> Self {
+        self.push_arg(arg.into())
+    }
+
+    /// Add an optional argument to the command.
+    ///
+    /// Optional arguments can be left out when calling the command. If they
+    /// are, the default value is passed into the command function instead, so
+    /// the function always receives every argument. Optional arguments must be
+    /// added after all of the required arguments.
+    ///
+    /// # Arguments
+    /// * `name` - String slice that holds the name of the argument
+    /// * `default` - String slice that holds the value used when the argument
+    ///   is not provided
+    ///
+    /// # Examples
+    ///
+    /// Construct a new command with a required and an optional argument:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "copy",
+    ///     "Copy a file",
+    ///     example_cmd_fn
+    /// )
+    /// .arg("src")
+    /// .optional_arg("dest", ".");
+    /// ```
+    ///
+    /// The command can be called using:
+    ///
+    /// `[app_name] copy <SRC> [DEST]`
+    pub fn optional_arg(self, name: &str, default: &str) -> Self {
+        self.push_arg(CommandArg::new(name).optional(default))
+    }
+
+    /// Add a variadic argument to the command.
+    ///
+    /// Variadic arguments accept any number of values between `min` and `max`.
+    /// All of the values are passed into the command function, after the
+    /// values of the other arguments. A variadic argument must be the last
+    /// argument of the command.
+    ///
+    /// Everything after a `--` separator on the command line is treated as
+    /// an argument value, even if it starts with a dash. This lets the
+    /// command function receive the tokens untouched, e.g. to forward them
+    /// to another program. The tokens fill the arguments of the command like
+    /// any other value and count towards their amount, so a command that
+    /// forwards them should end with a variadic argument that has no maximum.
+    ///
+    /// # Arguments
+    /// * `name` - String slice that holds the name of the argument
+    /// * `min` - The minimum amount of values that must be provided
+    /// * `max` - The maximum amount of values that can be provided, or `None`
+    ///   for no limit
+    ///
+    /// # Examples
+    ///
+    /// Construct a new command that takes one or more files and a destination:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "copy",
+    ///     "Copy files into a folder",
+    ///     example_cmd_fn
+    /// )
+    /// .arg("dest")
+    /// .variadic_arg("files", 1, None);
+    /// ```
+    ///
+    /// The command can be called using:
+    ///
+    /// `[app_name] copy <DEST> <FILES>...`
+    pub fn variadic_arg(self, name: &str, min: usize, max: Option<usize>) -> Self {
+        self.push_arg(CommandArg::new(name).variadic(min, max))
+    }
+
+    /// Add a typed argument to the command.
+    ///
+    /// Behaves the same as [arg](`Command::arg()`), but the argument is
+    /// parsed as the type `T` when the command line is parsed. If the
+    /// argument can't be parsed, an error is shown and the command function
+    /// is not called.
+    ///
+    /// # Arguments
+    /// * `name` - String slice that holds the name of the argument
+    ///
+    /// # Examples
+    ///
+    /// Construct a new command that takes two numbers:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn add_fn(input: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     let num_a: i32 = input.value(0).unwrap();
+    ///     let num_b: i32 = input.value(1).unwrap();
+    ///
+    ///     Ok(Some((num_a + num_b).to_string()))
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "add",
+    ///     "Add two numbers",
+    ///     add_fn
+    /// )
+    /// .arg_typed::<i32>("number_a")
+    /// .arg_typed::<i32>("number_b");
+    /// ```
+    ///
+    /// See [InputValues] for the typed getters.
+    pub fn arg_typed<T>(self, name: &str) -> Self
+    where
+        T: FromStr + 'static,
+        T::Err: Display,
+    {
+        self.push_arg(CommandArg::new(name).typed::<T>())
+    }
+
+    /// Add an argument to the command that is checked with a [ValueParser].
+    ///
+    /// Behaves the same as [arg_typed](`Command::arg_typed()`), but uses a
+    /// custom parser instead of the [FromStr] implementation of a type.
+    ///
+    /// # Arguments
+    /// * `name` - String slice that holds the name of the argument
+    /// * `parser` - The parser used to check the argument
+    pub fn arg_parser<P>(self, name: &str, parser: P) -> Self
+    where
+        P: ValueParser + 'static,
+    {
+        self.push_arg(CommandArg::new(name).parser(parser))
+    }
+
+    // Add an argument, making sure that required arguments never come after
+    // optional ones and that nothing comes after a variadic argument
+    fn push_arg(mut self, arg: CommandArg) -> Self {
+        if self.args.last().is_some_and(|last| last.is_variadic()) {
+            panic!(
+                "Arguments can't be added after a variadic argument: `{}`",
+                arg.name
+            );
+        }
+
+        if arg.min > 0 && self.args.iter().any(|a| a.min == 0) {
+            panic!(
+                "Required arguments can't be added after optional arguments: `{}`",
+                arg.name
+            );
+        }
+
+        self.args.push(arg);
+        self
+    }
+
+    // Returns the minimum amount of argument values the command accepts
+    pub(crate) fn min_args(&self) -> usize {
+        self.args.iter().map(|arg| arg.min).sum()
+    }
+
+    // Returns the maximum amount of argument values the command accepts, or
+    // `None` if there is no limit
+    pub(crate) fn max_args(&self) -> Option<usize> {
+        self.args.iter().map(|arg| arg.max).sum()
+    }
+
+    // Returns the argument that the value at the given index belongs to
+    pub(crate) fn arg_at(&self, index: usize) -> Option<&CommandArg> {
+        match self.args.get(index) {
+            Some(arg) if !arg.is_variadic() => Some(arg),
+            _ => self.args.last().filter(|arg| arg.is_variadic()),
+        }
+    }
+
+    /// Add a subcommand to the command.
+    ///
+    /// Subcommands are nested commands that are called by passing their alias
+    /// after the alias of their parent command. They have their own arguments,
+    /// options, and function. Subcommands can also have subcommands of their own.
+    ///
+    /// If the parent command also takes arguments, an argument that matches
+    /// the alias of a subcommand calls the subcommand instead. Pass the
+    /// argument after a `--` separator to give it to the parent command.
+    ///
+    /// # Arguments
+    /// * `command` - A `Command` struct holding the subcommand to be added
+    ///
+    /// # Examples
+    ///
+    /// Construct a `remote` command with an `add` subcommand:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "remote",
+    ///     "Manage remotes",
+    ///     example_cmd_fn
+    /// )
+    /// .subcommand(
+    ///     Command::new(
+    ///         "add",
+    ///         "Add a remote",
+    ///         example_cmd_fn
+    ///     )
+    ///     .arg("name")
+    ///     .arg("url")
+    /// );
+    /// ```
+    ///
+    /// The subcommand can be called using:
+    ///
+    /// `[app_name] remote add <NAME> <URL>`
+    pub fn subcommand(mut self, command: Command) -> Self {
+        self.subcommands.push(command);
+        self
+    }
+
+    /// Allow the arguments of the command to start with a dash.
+    ///
+    /// By default, every token that starts with a dash is treated as an option,
+    /// except for negative numbers. With this setting, tokens that start with
+    /// a dash but don't match any of the command options are passed into the
+    /// command function as arguments instead of causing an error. Tokens that
+    /// do match an option are still checked, so passing a value to a flag is
+    /// an error.
+    ///
+    /// # Examples
+    ///
+    /// Construct a new command that forwards its arguments to another program:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "exec",
+    ///     "Run a program",
+    ///     example_cmd_fn
+    /// )
+    /// .variadic_arg("args", 0, None)
+    /// .allow_hyphen_values();
+    /// ```
+    ///
+    /// The command can be called using:
+    ///
+    /// `[app_name] exec ls -la`
+    pub fn allow_hyphen_values(mut self) -> Self {
+        self.allow_hyphen_values = true;
+        self
+    }
+
+    /// Declare options of the command that can't be used together.
+    ///
+    /// Passing in more than one of the options is an error. The options are
+    /// given by their long aliases, without the leading dashes, and must
+    /// already have been added to the command. Global options can't be part
+    /// of a relationship.
+    ///
+    /// An option passed in on the command line takes precedence over the
+    /// environment variables, configuration file entries and default values
+    /// of the options it conflicts with, which are not filled in. Options that
+    /// are both filled in from these sources still conflict.
+    ///
+    /// # Arguments
+    /// * `aliases` - The long aliases of the options that conflict
+    ///
+    /// # Examples
+    ///
+    /// Construct a command where `--json` and `--table` can't be used together:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "list",
+    ///     "List the items",
+    ///     example_cmd_fn
+    /// )
+    /// .option(CommandOption::new("json", "Print JSON"))
+    /// .option(CommandOption::new("table", "Print a table"))
+    /// .conflicting_options(&["json", "table"]);
+    /// ```
+    pub fn conflicting_options(mut self, aliases: &[&str]) -> Self {
+        let aliases = self.relation_aliases(aliases);
+        self.relations.push(OptionRelation::Conflicts(aliases));
+        self
+    }
+
+    /// Declare that an option of the command requires another one.
+    ///
+    /// Passing in the option without the required option is an error. The
+    /// options are given by their long aliases, without the leading dashes,
+    /// and must already have been added to the command. Global options can't
+    /// be part of a relationship.
+    ///
+    /// The required option can also be filled in from its environment
+    /// variable, the configuration file or its default value.
+    ///
+    /// # Arguments
+    /// * `alias` - The long alias of the option that has the requirement
+    /// * `required` - The long alias of the option it requires
+    ///
+    /// # Examples
+    ///
+    /// Construct a command where `--key` can only be used along with `--cert`:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "serve",
+    ///     "Start the server",
+    ///     example_cmd_fn
+    /// )
+    /// .option(CommandOption::new("key", "Private key file").arg("file"))
+    /// .option(CommandOption::new("cert", "Certificate file").arg("file"))
+    /// .option_requires("key", "cert");
+    /// ```
+    pub fn option_requires(mut self, alias: &str, required: &str) -> Self {
+        let aliases = self.relation_aliases(&[alias, required]);
+        self.relations.push(OptionRelation::Requires(
+            aliases[0].clone(),
+            aliases[1].clone(),
+        ));
+        self
+    }
+
+    /// Declare options of the command where at least one must be passed in.
+    ///
+    /// Passing in none of the options is an error. The options are given by
+    /// their long aliases, without the leading dashes, and must already have
+    /// been added to the command. Global options can't be part of a
+    /// relationship.
+    ///
+    /// Options filled in from their environment variable, the configuration
+    /// file or their default value count towards the group too.
+    ///
+    /// # Arguments
+    /// * `aliases` - The long aliases of the options
+    ///
+    /// # Examples
+    ///
+    /// Construct a command that needs either `--id` or `--name`:
+    ///
+    /// ```
+    /// use climb::*;
+    ///
+    /// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+    ///     println!("my example function");
+    ///     Ok(None)
+    /// }
+    ///
+    /// let my_command = Command::new(
+    ///     "show",
+    ///     "Show a user",
+    ///     example_cmd_fn
+    /// )
+    /// .option(CommandOption::new("id", "The id of the user").arg("id"))
+    /// .option(CommandOption::new("name", "The name of the user").arg("name"))
+    /// .required_one_of(&["id", "name"]);
+    /// ```
+    pub fn required_one_of(mut self, aliases: &[&str]) -> Self {
+        let aliases = self.relation_aliases(aliases);
+        self.relations.push(OptionRelation::OneOf(aliases));
         self
     }
 
-    // If the command has the option, return a reference to it
-    pub(crate) fn has_option(&self, alias: &String) -> Option<&CommandOption> {
-        for option in &self.options {
-            let equals_alias_short = if let Some(alias_short) = &option.alias_short {
-                *alias == *alias_short
-            } else {
-                false
-            };
+    // Turns the long aliases passed into a relationship builder into the
+    // aliases of the command options, panicking if there is no such option.
+    // Global options belong to the app, so they are rejected here as well
+    fn relation_aliases(&self, aliases: &[&str]) -> Vec<String> {
+        aliases
+            .iter()
+            .map(|alias| {
+                let alias_long = format!("--{}", alias);
+                if !self
+                    .options
+                    .iter()
+                    .any(|option| option.alias_long == alias_long)
+                {
+                    panic!(
+                        "Options must be added to the command before their relationships: `{}`",
+                        alias_long
+                    );
+                }
+
+                alias_long
+            })
+            .collect()
+    }
+
+    // Returns true if the token is a negative number. These are treated as
+    // values unless the command has an option with a numeric short alias,
+    // which would make them ambiguous
+    pub(crate) fn is_negative_number(&self, token: &str) -> bool {
+        let Some(number) = token.strip_prefix('-') else {
+            return false;
+        };
+
+        let numeric_options = self.options.iter().any(|option| {
+            option
+                .alias_short
+                .as_ref()
+                .is_some_and(|alias| alias[1..].chars().all(|c| c.is_ascii_digit()))
+        });
+
+        let starts_numeric = number.starts_with(|c: char| c.is_ascii_digit() || c == '.');
+
+        !numeric_options && starts_numeric && number.parse::<f64>().is_ok()
+    }
+
+    // If the command has a subcommand with the given short or long alias,
+    // return a reference to it. If `prefixes` is true, a unique prefix of a
+    // long alias also matches, unless the command takes arguments that the
+    // prefix could be a value of. Returns the long aliases of the matching
+    // subcommands if the prefix is ambiguous
+    pub(crate) fn lookup_subcommand(
+        &self,
+        alias: &String,
+        prefixes: bool,
+    ) -> Result<Option<&Command>, Vec<String>> {
+        if let Some(command) = self
+            .subcommands
+            .iter()
+            .find(|command| command.has_alias(alias))
+        {
+            return Ok(Some(command));
+        }
+
+        match prefixes && self.args.is_empty() {
+            true => match_prefix(alias, &self.subcommands, |command| &command.alias_long),
+            false => Ok(None),
+        }
+    }
+
+    // Returns true if the command only groups its subcommands, so anything
+    // other than an option passed to it must name one of them
+    pub(crate) fn expects_subcommand(&self) -> bool {
+        !self.subcommands.is_empty() && self.args.is_empty()
+    }
+
+    // Returns the long and short aliases of the command
+    pub(crate) fn aliases(&self) -> Vec<&str> {
+        let mut aliases = vec![self.alias_long.as_str()];
+        aliases.extend(self.alias_short.as_deref());
+        aliases
+    }
+
+    // Returns true if the given alias is the short or long alias of the command
+    pub(crate) fn has_alias(&self, alias: &String) -> bool {
+        let equals_alias_short = if let Some(alias_short) = &self.alias_short {
+            *alias_short == *alias
+        } else {
+            false
+        };
+
+        *self.alias_long == *alias || equals_alias_short
+    }
+}
+
+// Finds the option with the given short or long alias. If `prefixes` is true,
+// a unique prefix of a long alias also matches. Returns the long aliases of
+// the matching options if the prefix is ambiguous
+pub(crate) fn find_option<'a>(
+    options: impl IntoIterator<Item = &'a CommandOption>,
+    alias: &str,
+    prefixes: bool,
+) -> Result<Option<&'a CommandOption>, Vec<String>> {
+    let options: Vec<&CommandOption> = options.into_iter().collect();
+
+    if let Some(option) = options
+        .iter()
+        .find(|option| option.aliases().contains(&alias))
+    {
+        return Ok(Some(option));
+    }
+
+    match prefixes {
+        true => Ok(match_prefix(alias, &options, |option| &option.alias_long)?.copied()),
+        false => Ok(None),
+    }
+}
+
+// Describes why an option token could not be split into options
+pub(crate) enum OptionTokenError {
+    // The alias of an option that doesn't exist
+    Unknown(String),
+    // The alias of an abbreviated option and the long aliases it could be
+    Ambiguous(String, Vec<String>),
+    // The alias of an option that was given a value but doesn't take one
+    UnexpectedValue(String),
+}
+
+// Splits an option token into the options it holds. Supports the GNU forms:
+// `--long`, `--long=value`, `-s`, `-svalue` and clustered short options such
+// as `-rf`. Returns each option along with the value that was attached to it,
+// if any. Used by both the parser and the completion of values
+pub(crate) fn split_option_token<'a>(
+    options: &[&'a CommandOption],
+    token: &str,
+    prefixes: bool,
+) -> Result<Vec<(&'a CommandOption, Option<String>)>, OptionTokenError> {
+    // Long option, possibly with an `=` joined value
+    if token.starts_with("--") {
+        let (alias, value) = match token.split_once('=') {
+            Some((alias, value)) => (alias, Some(value.to_string())),
+            None => (token, None),
+        };
 
-            if option.alias_long == *alias || equals_alias_short {
-                return Some(option);
+        let option = match find_option(options.iter().copied(), alias, prefixes) {
+            Ok(Some(option)) => option,
+            Ok(None) => return Err(OptionTokenError::Unknown(alias.to_string())),
+            Err(candidates) => {
+                return Err(OptionTokenError::Ambiguous(alias.to_string(), candidates))
             }
+        };
+
+        if value.is_some() && option.arg.is_none() {
+            return Err(OptionTokenError::UnexpectedValue(alias.to_string()));
+        }
+
+        return Ok(vec![(option, value)]);
+    }
+
+    // One or more short options. The first one that takes an argument
+    // uses the rest of the token as its value
+    let mut split = Vec::new();
+    for (index, c) in token.char_indices().skip(1) {
+        let alias = format!("-{}", c);
+        let Ok(Some(option)) = find_option(options.iter().copied(), &alias, false) else {
+            return Err(OptionTokenError::Unknown(alias));
+        };
+
+        if option.arg.is_some() {
+            let rest = &token[index + c.len_utf8()..];
+            split.push((option, (!rest.is_empty()).then(|| rest.to_string())));
+            break;
         }
 
-        None
+        split.push((option, None));
+    }
+
+    Ok(split)
+}
+
+// Finds the item whose long alias starts with the given prefix. Returns the
+// long aliases of every matching item if there is more than one
+pub(crate) fn match_prefix<'a, T>(
+    prefix: &str,
+    items: &'a [T],
+    alias_long: impl Fn(&T) -> &String,
+) -> Result<Option<&'a T>, Vec<String>> {
+    // The prefix must hold more than the dashes of an option
+    if prefix.trim_start_matches('-').is_empty() {
+        return Ok(None);
+    }
+
+    let matches: Vec<&T> = items
+        .iter()
+        .filter(|item| alias_long(item).starts_with(prefix))
+        .collect();
+
+    match matches.as_slice() {
+        [] => Ok(None),
+        [item] => Ok(Some(*item)),
+        _ => Err(matches
+            .iter()
+            .map(|item| alias_long(item).clone())
+            .collect()),
     }
 }
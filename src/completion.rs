@@ -0,0 +1,716 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::command::{split_option_token, Command, CommandArg, CommandOption, OptionTokenError};
+use crate::types::*;
+use crate::App;
+
+/// The shells that completion scripts can be generated for.
+///
+/// See [App::generate_completions](crate::App::generate_completions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(String::from("expected one of bash, zsh, fish")),
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+            Shell::Fish => write!(f, "fish"),
+        }
+    }
+}
+
+/// Describes how the values of an argument or option are completed.
+///
+/// Hints are resolved at runtime by the completion scripts, which call the
+/// hidden `__complete` command of the application with the partial command
+/// line. See [App::generate_completions](crate::App::generate_completions).
+///
+/// Options that take an argument complete file names if they have no hint.
+/// Arguments complete nothing if they have no hint, except in fish, which
+/// completes file names for them.
+///
+/// # Examples
+///
+/// Completing the names of branches with a custom function:
+///
+/// ```
+/// use climb::*;
+///
+/// let my_option = CommandOption::new(
+///     "branch",
+///     "The branch to check out"
+/// )
+/// .arg("branch")
+/// .hint(ValueHint::Custom(Box::new(|_: &CompletionContext| {
+///     vec![String::from("main"), String::from("develop")]
+/// })));
+/// ```
+pub enum ValueHint {
+    /// Complete the names of files and folders
+    File,
+
+    /// Complete the names of folders
+    Directory,
+
+    /// Complete one of the given values
+    Choices(Vec<String>),
+
+    /// Complete the values returned by a function
+    ///
+    /// The function is given the state of the command line that is being
+    /// completed. Only the returned values that start with the word that is
+    /// being completed are shown.
+    Custom(CompletionFunction),
+}
+
+/// A function that returns the values of a [ValueHint::Custom] hint
+pub type CompletionFunction = Box<dyn Fn(&CompletionContext) -> Vec<String>>;
+
+/// Holds the state of a command line that is being completed.
+///
+/// Passed into [ValueHint::Custom] functions so that the values they return
+/// can depend on what has already been typed.
+pub struct CompletionContext {
+    pub(crate) current: String,
+    pub(crate) command: String,
+    pub(crate) input: FunctionInput,
+    pub(crate) options: FunctionOptions,
+}
+
+impl CompletionContext {
+    /// Returns the partial word that is being completed
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    /// Returns the long aliases of the command that is being completed and
+    /// its parent commands, separated by spaces (e.g. `remote add`)
+    ///
+    /// Returns an empty string if no command has been typed yet.
+    pub fn command_name(&self) -> &str {
+        &self.command
+    }
+
+    /// Returns the arguments that have been typed so far
+    pub fn input(&self) -> &FunctionInput {
+        &self.input
+    }
+
+    /// Returns the options that have been typed so far
+    pub fn options(&self) -> &FunctionOptions {
+        &self.options
+    }
+}
+
+// A command, or the application itself, that completions are generated for
+struct CompletionNode<'a> {
+    // The application name followed by the long aliases of the commands,
+    // separated by spaces, e.g. `app remote add`
+    path: String,
+    options: Vec<&'a CommandOption>,
+    args: &'a [CommandArg],
+    subcommands: &'a [Command],
+}
+
+// Collects the application and all of its commands and subcommands
+fn collect_nodes(app: &App) -> Vec<CompletionNode<'_>> {
+    let mut nodes = vec![CompletionNode {
+        path: app.name.clone(),
+        options: app.options.iter().chain(&app.global_options).collect(),
+        args: &[],
+        subcommands: &app.commands,
+    }];
+
+    let mut index = 0;
+    while index < nodes.len() {
+        let path = nodes[index].path.clone();
+        for command in nodes[index].subcommands {
+            nodes.push(CompletionNode {
+                path: format!("{} {}", path, command.alias_long),
+                options: command_options(app, command),
+                args: &command.args,
+                subcommands: &command.subcommands,
+            });
+        }
+        index += 1;
+    }
+
+    nodes
+}
+
+// Returns the options of a command followed by the global options
+fn command_options<'a>(app: &'a App, command: &'a Command) -> Vec<&'a CommandOption> {
+    command.options.iter().chain(&app.global_options).collect()
+}
+
+// Returns the options that can be passed to a command, or to the application
+// before the command if there is none yet
+fn node_options<'a>(app: &'a App, command: Option<&'a Command>) -> Vec<&'a CommandOption> {
+    match command {
+        Some(command) => command_options(app, command),
+        None => app.options.iter().chain(&app.global_options).collect(),
+    }
+}
+
+// Turns the application name into a valid shell function name
+fn function_name(app: &App) -> String {
+    let name: String = app
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("_{}", name)
+}
+
+// Escapes a string so that it can be placed inside single quotes
+fn escape_single_quotes(value: &str) -> String {
+    value.replace('\'', "'\\''")
+}
+
+// Writes the arms of the bash and zsh `case` statement that walks the words
+// on the command line to find the command that is being completed
+fn write_sh_path_cases(nodes: &[CompletionNode], out: &mut impl Write) -> io::Result<()> {
+    for node in nodes {
+        for command in node.subcommands {
+            let patterns: Vec<String> = command
+                .aliases()
+                .iter()
+                .map(|alias| format!("\"{}:{}\"", node.path, alias))
+                .collect();
+            writeln!(
+                out,
+                "            {}) cmd_path=\"{} {}\" ;;",
+                patterns.join("|"),
+                node.path,
+                command.alias_long
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the values that complete the last of the given words. The words
+// are the command line that is being completed, without the application
+// name. Only values of arguments and options are completed; the commands and
+// options themselves are completed by the generated scripts
+pub(crate) fn complete(app: &App, words: &[String]) -> Vec<String> {
+    let (words, split_current) = join_option_values(words);
+    let Some((current, previous)) = words.split_last() else {
+        return vec![];
+    };
+
+    let mut command_path = Vec::<&Command>::new();
+    let mut input = FunctionInput::new();
+    let mut options = FunctionOptions::new();
+    let mut expecting: Option<&CommandOption> = None;
+    let mut after_separator = false;
+    // Subcommands can only follow their parent command directly
+    let mut walking = true;
+
+    // Walk the words that have already been typed to find the command and
+    // the arguments and options that were passed to it, the same way as
+    // the parser
+    for word in previous {
+        let command = command_path.last().copied();
+
+        if let Some(option) = expecting.take() {
+            options.push(FunctionOption(
+                option.alias_long.clone(),
+                Some(word.clone()),
+            ));
+            continue;
+        }
+
+        let is_option = !after_separator
+            && word.starts_with('-')
+            && word != "-"
+            && !command.is_some_and(|command| command.is_negative_number(word));
+
+        if is_option && word == "--" {
+            after_separator = true;
+            walking = false;
+        } else if is_option {
+            let node_options = node_options(app, command);
+            match split_option_token(&node_options, word, app.infer_prefixes) {
+                Ok(split) => {
+                    for (option, value) in split {
+                        match value {
+                            None if option.arg.is_some() => expecting = Some(option),
+                            value => options.push(FunctionOption(option.alias_long.clone(), value)),
+                        }
+                    }
+                }
+                // Unknown options are arguments if the command allows it
+                Err(OptionTokenError::Unknown(alias))
+                    if command.is_some_and(|command| command.allow_hyphen_values)
+                        && word.starts_with(&alias) =>
+                {
+                    input.push(word.clone())
+                }
+                Err(_) => {}
+            }
+
+            walking = walking && command.is_none();
+        } else {
+            let subcommand = match command {
+                None => app.lookup_command(word),
+                Some(command) if walking => command.lookup_subcommand(word, app.infer_prefixes),
+                Some(_) => Ok(None),
+            };
+
+            match subcommand {
+                Ok(Some(subcommand)) => command_path.push(subcommand),
+                _ => {
+                    input.push(word.clone());
+                    walking = false;
+                }
+            }
+        }
+    }
+
+    let command = command_path.last().copied();
+    let mut context = CompletionContext {
+        current: current.clone(),
+        command: crate::help::format_command_path(&command_path),
+        input,
+        options,
+    };
+
+    // Complete the argument of an option
+    if let Some(option) = expecting {
+        return complete_option_value(option, &context);
+    }
+
+    let is_option = !after_separator
+        && current.starts_with('-')
+        && !command.is_some_and(|command| command.is_negative_number(current));
+
+    if is_option {
+        // Complete the argument of an option attached to it, e.g. `--opt=val`
+        // or `-oval`
+        let node_options = node_options(app, command);
+        let Ok(split) = split_option_token(&node_options, current, app.infer_prefixes) else {
+            return vec![];
+        };
+
+        let Some((option, Some(value))) = split.last() else {
+            return vec![];
+        };
+
+        // Bash only replaces the part of the word after the `=`
+        let prefix = match split_current {
+            true => "",
+            false => &current[..current.len() - value.len()],
+        };
+
+        context.current = value.clone();
+        return complete_option_value(option, &context)
+            .into_iter()
+            .map(|value| format!("{}{}", prefix, value))
+            .collect();
+    }
+
+    // Complete the value of the next argument of the command
+    let Some(arg) = command.and_then(|command| command.arg_at(context.input.len())) else {
+        return vec![];
+    };
+
+    // Arguments without a hint complete their choices, if they have any
+    match &arg.hint {
+        Some(hint) => complete_hint(hint, &context),
+        None => filter_values(arg.choices.clone(), &context),
+    }
+}
+
+// Bash splits words on `=`, so `--opt=value` is passed in as `--opt`, `=`
+// and `value`. Joins them back together, and returns true if the last word
+// was joined
+fn join_option_values(words: &[String]) -> (Vec<String>, bool) {
+    let mut joined = Vec::<String>::new();
+    let mut last_joined = false;
+    let mut it = words.iter();
+
+    while let Some(word) = it.next() {
+        let after_long_option = joined.last().is_some_and(|last: &String| {
+            last.starts_with("--") && last.len() > 2 && !last.contains('=')
+        });
+        let after_separator = joined.iter().any(|word| word == "--");
+
+        last_joined = word == "=" && after_long_option && !after_separator;
+        if !last_joined {
+            joined.push(word.clone());
+            continue;
+        }
+
+        let last = joined.last_mut().unwrap();
+        last.push('=');
+        last.extend(it.next().map(String::as_str));
+    }
+
+    (joined, last_joined)
+}
+
+// Options that take an argument complete their choices if they have no
+// hint, or file names if they have neither
+fn complete_option_value(option: &CommandOption, context: &CompletionContext) -> Vec<String> {
+    match &option.hint {
+        Some(hint) => complete_hint(hint, context),
+        None if !option.choices.is_empty() => filter_values(option.choices.clone(), context),
+        None => complete_hint(&ValueHint::File, context),
+    }
+}
+
+// Returns the values of a hint that start with the word being completed
+fn complete_hint(hint: &ValueHint, context: &CompletionContext) -> Vec<String> {
+    match hint {
+        ValueHint::File => complete_path(&context.current, false),
+        ValueHint::Directory => complete_path(&context.current, true),
+        ValueHint::Choices(choices) => filter_values(choices.clone(), context),
+        ValueHint::Custom(function) => filter_values(function(context), context),
+    }
+}
+
+// Returns the values that start with the word being completed
+fn filter_values(values: Vec<String>, context: &CompletionContext) -> Vec<String> {
+    values
+        .into_iter()
+        .filter(|value| value.starts_with(&context.current))
+        .collect()
+}
+
+// Lists the files and folders that start with the given partial path.
+// Folders are followed by a `/`
+fn complete_path(current: &str, only_dirs: bool) -> Vec<String> {
+    let (dir, prefix) = match current.rfind('/') {
+        Some(index) => current.split_at(index + 1),
+        None => ("", current),
+    };
+
+    let search_dir = if dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir)
+    };
+    let Ok(entries) = fs::read_dir(search_dir) else {
+        return vec![];
+    };
+
+    let mut values: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.path().is_dir();
+
+            // Hidden files are only shown if the prefix starts with a dot
+            let hidden = name.starts_with('.') && !prefix.starts_with('.');
+            if !name.starts_with(prefix) || hidden || (only_dirs && !is_dir) {
+                return None;
+            }
+
+            Some(format!("{}{}{}", dir, name, if is_dir { "/" } else { "" }))
+        })
+        .collect();
+
+    values.sort();
+    values
+}
+
+pub(crate) fn generate(app: &App, shell: Shell, out: &mut impl Write) -> io::Result<()> {
+    let nodes = collect_nodes(app);
+
+    match shell {
+        Shell::Bash => generate_bash(app, &nodes, out),
+        Shell::Zsh => generate_zsh(app, &nodes, out),
+        Shell::Fish => generate_fish(app, &nodes, out),
+    }
+}
+
+fn generate_bash(app: &App, nodes: &[CompletionNode], out: &mut impl Write) -> io::Result<()> {
+    let function = function_name(app);
+
+    writeln!(out, "{}() {{", function)?;
+    writeln!(out, "    local cur prev cmd_path opts i")?;
+    writeln!(out, "    local -a values")?;
+    writeln!(out, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+    writeln!(out, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+    // Bash splits `--opt=value` into `--opt`, `=` and `value`
+    writeln!(out, "    if [[ \"${{prev}}\" == \"=\" ]]; then")?;
+    writeln!(out, "        prev=\"${{COMP_WORDS[COMP_CWORD-2]}}\"")?;
+    writeln!(out, "    fi")?;
+    writeln!(out, "    cmd_path=\"{}\"", app.name)?;
+    writeln!(out)?;
+
+    // Find the command that is being completed
+    writeln!(out, "    for ((i = 1; i < COMP_CWORD; i++)); do")?;
+    writeln!(out, "        case \"${{cmd_path}}:${{COMP_WORDS[i]}}\" in")?;
+    write_sh_path_cases(nodes, out)?;
+    writeln!(out, "        esac")?;
+    writeln!(out, "    done")?;
+    writeln!(out)?;
+
+    // Ask the application for the values of arguments and options
+    writeln!(
+        out,
+        "    mapfile -t values < <(\"${{COMP_WORDS[0]}}\" __complete -- \"${{COMP_WORDS[@]:1:COMP_CWORD}}\" 2>/dev/null)"
+    )?;
+    writeln!(out)?;
+
+    // Complete the options and subcommands of the command
+    writeln!(out, "    case \"${{cmd_path}}\" in")?;
+    for node in nodes {
+        writeln!(out, "        \"{}\")", node.path)?;
+
+        // Only complete values after options that take an argument
+        let arg_aliases: Vec<&str> = node
+            .options
+            .iter()
+            .copied()
+            .filter(|option| option.arg.is_some())
+            .flat_map(CommandOption::aliases)
+            .collect();
+        if !arg_aliases.is_empty() {
+            writeln!(out, "            case \"${{prev}}\" in")?;
+            writeln!(
+                out,
+                "                {}) COMPREPLY=(\"${{values[@]}}\"); return 0 ;;",
+                arg_aliases.join("|")
+            )?;
+            writeln!(out, "            esac")?;
+        }
+
+        let mut words: Vec<&str> = node
+            .options
+            .iter()
+            .copied()
+            .flat_map(CommandOption::aliases)
+            .collect();
+        words.extend(node.subcommands.iter().flat_map(Command::aliases));
+        writeln!(out, "            opts=\"{}\"", words.join(" "))?;
+        writeln!(out, "            ;;")?;
+    }
+    writeln!(out, "    esac")?;
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "    COMPREPLY=($(compgen -W \"${{opts}}\" -- \"${{cur}}\"))"
+    )?;
+    writeln!(out, "    if [[ \"${{cur}}\" != -* ]]; then")?;
+    writeln!(out, "        COMPREPLY+=(\"${{values[@]}}\")")?;
+    writeln!(out, "    fi")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "complete -F {} {}", function, app.name)?;
+
+    Ok(())
+}
+
+fn generate_zsh(app: &App, nodes: &[CompletionNode], out: &mut impl Write) -> io::Result<()> {
+    let function = function_name(app);
+
+    // Escapes an entry of the `_describe` completion function
+    let describe_entry = |name: &str, desc: &str| {
+        format!(
+            "'{}:{}'",
+            escape_single_quotes(&name.replace(':', "\\:")),
+            escape_single_quotes(desc)
+        )
+    };
+
+    writeln!(out, "#compdef {}", app.name)?;
+    writeln!(out)?;
+    writeln!(out, "{}() {{", function)?;
+    writeln!(out, "    local cmd_path=\"{}\" i", app.name)?;
+    writeln!(out, "    local -a candidates values")?;
+    writeln!(out)?;
+
+    // Find the command that is being completed
+    writeln!(out, "    for ((i = 2; i < CURRENT; i++)); do")?;
+    writeln!(out, "        case \"${{cmd_path}}:${{words[i]}}\" in")?;
+    write_sh_path_cases(nodes, out)?;
+    writeln!(out, "        esac")?;
+    writeln!(out, "    done")?;
+    writeln!(out)?;
+
+    // Ask the application for the values of arguments and options
+    writeln!(
+        out,
+        "    values=(${{(f)\"$(${{words[1]}} __complete -- \"${{(@)words[2,CURRENT]}}\" 2>/dev/null)\"}})"
+    )?;
+    writeln!(out)?;
+
+    // Complete the options and subcommands of the command
+    writeln!(out, "    case \"${{cmd_path}}\" in")?;
+    for node in nodes {
+        writeln!(out, "        \"{}\")", node.path)?;
+
+        // Only complete values after options that take an argument
+        let arg_aliases: Vec<&str> = node
+            .options
+            .iter()
+            .copied()
+            .filter(|option| option.arg.is_some())
+            .flat_map(CommandOption::aliases)
+            .collect();
+        if !arg_aliases.is_empty() {
+            writeln!(out, "            case \"${{words[CURRENT-1]}}\" in")?;
+            writeln!(
+                out,
+                "                {}) compadd -a values; return ;;",
+                arg_aliases.join("|")
+            )?;
+            writeln!(out, "            esac")?;
+        }
+
+        let mut entries = Vec::<String>::new();
+        for option in &node.options {
+            for alias in option.aliases() {
+                entries.push(describe_entry(alias, &option.description));
+            }
+        }
+        for command in node.subcommands {
+            for alias in command.aliases() {
+                entries.push(describe_entry(alias, &command.desc));
+            }
+        }
+
+        writeln!(out, "            candidates=({})", entries.join(" "))?;
+        writeln!(out, "            ;;")?;
+    }
+    writeln!(out, "    esac")?;
+    writeln!(out)?;
+
+    writeln!(out, "    _describe 'command' candidates")?;
+    writeln!(
+        out,
+        "    [[ \"${{words[CURRENT]}}\" != -* ]] && compadd -a values"
+    )?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "if [ \"$funcstack[1]\" = \"{}\" ]; then", function)?;
+    writeln!(out, "    {} \"$@\"", function)?;
+    writeln!(out, "else")?;
+    writeln!(out, "    compdef {} {}", function, app.name)?;
+    writeln!(out, "fi")?;
+
+    Ok(())
+}
+
+fn generate_fish(app: &App, nodes: &[CompletionNode], out: &mut impl Write) -> io::Result<()> {
+    let function = format!("_{}_cmd_path", function_name(app));
+    let values_function = format!("_{}_values", function_name(app));
+
+    // Find the command that is being completed
+    writeln!(out, "function {}", function)?;
+    writeln!(out, "    set -l cmd_path \"{}\"", app.name)?;
+    writeln!(out, "    for token in (commandline -opc)[2..-1]")?;
+    writeln!(out, "        switch \"$cmd_path:$token\"")?;
+    for node in nodes {
+        for command in node.subcommands {
+            let patterns: Vec<String> = command
+                .aliases()
+                .iter()
+                .map(|alias| format!("\"{}:{}\"", node.path, alias))
+                .collect();
+            writeln!(out, "            case {}", patterns.join(" "))?;
+            writeln!(
+                out,
+                "                set cmd_path \"{} {}\"",
+                node.path, command.alias_long
+            )?;
+        }
+    }
+    writeln!(out, "        end")?;
+    writeln!(out, "    end")?;
+    writeln!(out, "    echo $cmd_path")?;
+    writeln!(out, "end")?;
+    writeln!(out)?;
+
+    // Ask the application for the values of arguments and options
+    writeln!(out, "function {}", values_function)?;
+    writeln!(out, "    set -l tokens (commandline -opc)")?;
+    writeln!(
+        out,
+        "    $tokens[1] __complete -- $tokens[2..-1] (commandline -ct) 2>/dev/null"
+    )?;
+    writeln!(out, "end")?;
+    writeln!(out)?;
+
+    // Complete the options and subcommands of every command
+    for node in nodes {
+        let condition = format!("-n 'test ({}) = \"{}\"'", function, node.path);
+
+        // Fish completes file names unless told otherwise. Keep them for
+        // commands with an argument that has no hint or choices
+        let unhinted = node
+            .args
+            .iter()
+            .any(|arg| arg.hint.is_none() && arg.choices.is_empty());
+        if !unhinted {
+            writeln!(out, "complete -c {} {} -f", app.name, condition)?;
+        }
+
+        // Complete the values of arguments
+        if node.path != app.name {
+            writeln!(
+                out,
+                "complete -c {} {} -a '({})'",
+                app.name, condition, values_function
+            )?;
+        }
+
+        for option in &node.options {
+            let mut line = format!("complete -c {} {}", app.name, condition);
+
+            line.push_str(format!(" -l {}", &option.alias_long[2..]).as_str());
+            if let Some(alias_short) = &option.alias_short {
+                line.push_str(format!(" -s {}", &alias_short[1..]).as_str());
+            }
+
+            // Complete the values of options that take an argument
+            if option.arg.is_some() {
+                line.push_str(format!(" -r -a '({})'", values_function).as_str());
+            }
+
+            line.push_str(format!(" -d '{}'", option.description.replace('\'', "\\'")).as_str());
+            writeln!(out, "{}", line)?;
+        }
+
+        for command in node.subcommands {
+            writeln!(
+                out,
+                "complete -c {} {} -a '{}' -d '{}'",
+                app.name,
+                condition,
+                command.aliases().join(" "),
+                command.desc.replace('\'', "\\'")
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::error::ParseError;
+
+// Holds the option defaults read from a configuration file.
+//
+// The file uses a simple TOML/INI-like format. Sections are named after the
+// long alias of a command, with subcommands separated by dots. Keys are the
+// long aliases of options without the leading dashes. Keys that come before
+// the first section apply to every command:
+//
+//     # Applies to every command
+//     verbose = true
+//
+//     [deploy]
+//     region = "eu-west-1"
+//
+//     [remote.add]
+//     fetch = true
+#[derive(Default)]
+pub(crate) struct Config {
+    // Maps the command path (long aliases separated by spaces, or an empty
+    // string for the top of the file) to the keys and values of its section
+    sections: HashMap<String, HashMap<String, String>>,
+}
+
+impl Config {
+    // Reads the configuration file at the given path. A missing file is
+    // treated as an empty configuration
+    pub(crate) fn load(path: &Path) -> Result<Self, ParseError> {
+        let config_error = |line: Option<usize>, message: String| ParseError::InvalidConfig {
+            path: path.display().to_string(),
+            line,
+            message,
+        };
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(config_error(None, e.to_string())),
+        };
+
+        let mut config = Config::default();
+        let mut section = String::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            // Skip empty lines and comments
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            // Section header, e.g. `[remote.add]`
+            if let Some(name) = line.strip_prefix('[') {
+                let Some(name) = name.strip_suffix(']') else {
+                    return Err(config_error(
+                        Some(index + 1),
+                        String::from("section header is missing `]`"),
+                    ));
+                };
+
+                section = name.trim().replace('.', " ");
+                continue;
+            }
+
+            // Key and value pair, e.g. `region = "eu-west-1"`
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(config_error(
+                    Some(index + 1),
+                    format!("expected `key = value`, found `{}`", line),
+                ));
+            };
+
+            config
+                .sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), unquote(value.trim()).to_string());
+        }
+
+        Ok(config)
+    }
+
+    // Returns the value of the key for the given command path. Values in the
+    // section of the command take precedence over the top of the file
+    pub(crate) fn get(&self, command: &str, key: &str) -> Option<String> {
+        [command, ""]
+            .iter()
+            .find_map(|section| self.sections.get(*section)?.get(key))
+            .cloned()
+    }
+}
+
+// Removes matching quotes from around a value
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+
+    value
+}
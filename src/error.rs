@@ -0,0 +1,305 @@
+use std::error::Error;
+use std::fmt;
+
+/// Describes why the command line arguments could not be parsed.
+///
+/// Returned by [App::try_parse](crate::App::try_parse) and
+/// [App::try_run_custom](crate::App::try_run_custom) so that applications
+/// can react to parsing failures in code instead of only seeing a help menu.
+///
+/// The `command` fields hold the long aliases of the command that was
+/// being parsed and of its parent commands. For example, `["remote", "add"]`
+/// for the `add` subcommand of the `remote` command.
+///
+/// More variants may be added in future versions, so matching on the error
+/// needs a wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// No arguments were passed to the application
+    MissingCommand,
+
+    /// The help menu was requested with `-h` or `--help`
+    ///
+    /// `command` is `None` if the help menu of the application was requested.
+    HelpRequested { command: Option<Vec<String>> },
+
+    /// The version was requested with `-v` or `--version`
+    VersionRequested,
+
+    /// The given command does not exist
+    ///
+    /// `command` holds the parent command if the given command was passed to
+    /// a command that has subcommands and takes no arguments, or `None` if it
+    /// was passed to the application. `suggestions` holds the aliases of the
+    /// commands that are closest to the given one, if any are similar enough.
+    UnknownCommand {
+        token: String,
+        command: Option<Vec<String>>,
+        suggestions: Vec<String>,
+    },
+
+    /// The given option does not exist
+    ///
+    /// `command` is `None` if the option was passed to the application
+    /// instead of a command. `suggestions` holds the aliases of the options
+    /// that are closest to the given one, if any are similar enough.
+    UnknownOption {
+        token: String,
+        command: Option<Vec<String>>,
+        suggestions: Vec<String>,
+    },
+
+    /// The given command is a prefix of more than one command
+    ///
+    /// Only returned if [prefix matching](crate::App::infer_prefixes) is
+    /// enabled. `command` holds the parent command, or `None` if the prefix
+    /// was passed to the application. `candidates` holds the long aliases of
+    /// the matching commands.
+    AmbiguousCommand {
+        token: String,
+        command: Option<Vec<String>>,
+        candidates: Vec<String>,
+    },
+
+    /// The given option is a prefix of more than one option
+    ///
+    /// Only returned if [prefix matching](crate::App::infer_prefixes) is
+    /// enabled. `command` is `None` if the option was passed to the
+    /// application instead of a command. `candidates` holds the long aliases
+    /// of the matching options.
+    AmbiguousOption {
+        token: String,
+        command: Option<Vec<String>>,
+        candidates: Vec<String>,
+    },
+
+    /// An argument was attached to an option that doesn't take one, e.g. `--force=yes`
+    UnexpectedOptionArgument { token: String, command: Vec<String> },
+
+    /// An option that takes an argument was passed in without one
+    MissingOptionArgument {
+        token: String,
+        command: Vec<String>,
+        argument: String,
+    },
+
+    /// An option that isn't repeatable was passed in more than once
+    DuplicateOption { token: String, command: Vec<String> },
+
+    /// A required option was not passed in
+    MissingRequiredOption { token: String, command: Vec<String> },
+
+    /// Options that can't be used together were passed in
+    ///
+    /// `token` and `other` hold the long aliases of two of the conflicting
+    /// options. See [Command::conflicting_options](crate::Command::conflicting_options).
+    ConflictingOptions {
+        token: String,
+        other: String,
+        command: Vec<String>,
+    },
+
+    /// An option was passed in without an option that it requires
+    ///
+    /// `required` holds the long alias of the option that is missing. See
+    /// [Command::option_requires](crate::Command::option_requires).
+    MissingOptionDependency {
+        token: String,
+        required: String,
+        command: Vec<String>,
+    },
+
+    /// None of the options where at least one is required were passed in
+    ///
+    /// `tokens` holds the long aliases of the options. See
+    /// [Command::required_one_of](crate::Command::required_one_of).
+    MissingOneOfOptions {
+        tokens: Vec<String>,
+        command: Vec<String>,
+    },
+
+    /// The amount of arguments passed in is not accepted by the command
+    ///
+    /// `max` is `None` if the command accepts any amount of arguments
+    /// above `min`.
+    WrongArgumentCount {
+        command: Vec<String>,
+        min: usize,
+        max: Option<usize>,
+        actual: usize,
+    },
+
+    /// The value of an argument or option is not valid
+    ///
+    /// `argument` holds the name of the argument (e.g. `<COUNT>`) or the
+    /// option (e.g. `--count`) that the value was passed to, and `message`
+    /// holds the reason why it is not valid.
+    InvalidValue {
+        token: String,
+        command: Vec<String>,
+        argument: String,
+        message: String,
+    },
+
+    /// The configuration file of the application could not be read
+    ///
+    /// `line` holds the line of the file that could not be parsed, or `None`
+    /// if the file could not be read at all.
+    InvalidConfig {
+        path: String,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl ParseError {
+    /// Returns the command that was being parsed when the error occurred
+    ///
+    /// Returns `None` if the error occurred before a command was found.
+    pub fn command(&self) -> Option<&[String]> {
+        match self {
+            ParseError::MissingCommand
+            | ParseError::VersionRequested
+            | ParseError::InvalidConfig { .. } => None,
+            ParseError::HelpRequested { command }
+            | ParseError::UnknownCommand { command, .. }
+            | ParseError::UnknownOption { command, .. }
+            | ParseError::AmbiguousCommand { command, .. }
+            | ParseError::AmbiguousOption { command, .. } => command.as_deref(),
+            ParseError::UnexpectedOptionArgument { command, .. }
+            | ParseError::MissingOptionArgument { command, .. }
+            | ParseError::DuplicateOption { command, .. }
+            | ParseError::MissingRequiredOption { command, .. }
+            | ParseError::ConflictingOptions { command, .. }
+            | ParseError::MissingOptionDependency { command, .. }
+            | ParseError::MissingOneOfOptions { command, .. }
+            | ParseError::WrongArgumentCount { command, .. }
+            | ParseError::InvalidValue { command, .. } => Some(command),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingCommand => write!(f, "No command provided"),
+            ParseError::HelpRequested { .. } => write!(f, "Help information requested"),
+            ParseError::VersionRequested => write!(f, "Version requested"),
+            ParseError::UnknownCommand {
+                token, suggestions, ..
+            } => {
+                write!(f, "The given command does not exist: `{}`", token)?;
+                write_suggestions(f, suggestions)
+            }
+            ParseError::UnknownOption {
+                token, suggestions, ..
+            } => {
+                write!(f, "The given option does not exist: `{}`", token)?;
+                write_suggestions(f, suggestions)
+            }
+            ParseError::AmbiguousCommand {
+                token, candidates, ..
+            } => write!(
+                f,
+                "The given command is ambiguous: `{}` (could be {})",
+                token,
+                format_alternatives(candidates)
+            ),
+            ParseError::AmbiguousOption {
+                token, candidates, ..
+            } => write!(
+                f,
+                "The given option is ambiguous: `{}` (could be {})",
+                token,
+                format_alternatives(candidates)
+            ),
+            ParseError::UnexpectedOptionArgument { token, .. } => {
+                write!(f, "Option does not take an argument: `{}`", token)
+            }
+            ParseError::MissingOptionArgument {
+                token, argument, ..
+            } => write!(f, "{} not provided for option: `{}`", argument, token),
+            ParseError::DuplicateOption { token, .. } => {
+                write!(f, "Option provided more than once: `{}`", token)
+            }
+            ParseError::MissingRequiredOption { token, .. } => {
+                write!(f, "Required option not provided: `{}`", token)
+            }
+            ParseError::ConflictingOptions { token, other, .. } => write!(
+                f,
+                "Options cannot be used together: `{}` and `{}`",
+                token, other
+            ),
+            ParseError::MissingOptionDependency {
+                token, required, ..
+            } => write!(f, "Option `{}` requires option: `{}`", token, required),
+            ParseError::MissingOneOfOptions { tokens, .. } => write!(
+                f,
+                "One of these options must be provided: {}",
+                format_alternatives(tokens)
+            ),
+            ParseError::WrongArgumentCount {
+                command,
+                min,
+                max,
+                actual,
+            } => {
+                let expected = match max {
+                    Some(max) if max == min => format!("{}", min),
+                    Some(max) => format!("{} to {}", min, max),
+                    None => format!("at least {}", min),
+                };
+
+                write!(
+                    f,
+                    "Incorrect amount of arguments provided for command: {} (expected {}, got {})",
+                    command.join(" "),
+                    expected,
+                    actual
+                )
+            }
+            ParseError::InvalidValue {
+                token,
+                argument,
+                message,
+                ..
+            } => write!(f, "Invalid value `{}` for {}: {}", token, argument, message),
+            ParseError::InvalidConfig {
+                path,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "Invalid configuration file `{}` on line {}: {}",
+                path, line, message
+            ),
+            ParseError::InvalidConfig {
+                path,
+                line: None,
+                message,
+            } => write!(f, "Invalid configuration file `{}`: {}", path, message),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+// Writes the suggestions of an unknown command or option, e.g.
+// ` (did you mean `status` or `stash`?)`
+fn write_suggestions(f: &mut fmt::Formatter<'_>, suggestions: &[String]) -> fmt::Result {
+    match suggestions.is_empty() {
+        true => Ok(()),
+        false => write!(f, " (did you mean {}?)", format_alternatives(suggestions)),
+    }
+}
+
+// Formats a list of aliases, e.g. `status`, `stash` or `stage`
+fn format_alternatives(aliases: &[String]) -> String {
+    let quoted: Vec<String> = aliases.iter().map(|alias| format!("`{}`", alias)).collect();
+
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => quoted.join(""),
+    }
+}
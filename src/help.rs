@@ -1,80 +1,252 @@
 use crate::{
-    command::{Command, CommandOption},
-    App,
+    command::{Command, CommandArg, CommandOption, OptionRelation},
+    App, ParseError,
 };
 
 use colored::*;
 
-pub(crate) fn print_help_app(app: &App, error_msg: Option<String>) {
-    println!("{}\n", app.desc);
+pub(crate) fn format_help_app(app: &App, error_msg: Option<String>) -> String {
+    let mut help_str = format!("{}\n\n", app.desc);
 
-    println!("USAGE:\n\t{} [OPTIONS] [COMMAND]", app.name);
+    help_str.push_str(format!("USAGE:\n\t{} [OPTIONS] [COMMAND]\n", app.name).as_str());
 
-    println!("\nOPTIONS:");
+    help_str.push_str("\nOPTIONS:\n");
     for option in &app.options {
         // Print the short alias first, if possible
-        println!("{}", format_option_str(option));
+        help_str.push_str(format!("{}\n", format_option_str(option)).as_str());
     }
 
-    println!("\nCOMMANDS:");
+    help_str.push_str(&format_global_options_str(app));
+
+    help_str.push_str("\nCOMMANDS:\n");
     for command in &app.commands {
         // Print the long aliases first
-        println!("{}", format_command_str(command));
+        help_str.push_str(format!("{}\n", format_command_str(command)).as_str());
     }
 
-    println!(
-        "\nRun `{} [COMMAND] --help` to see help information for a specific command",
-        app.name
+    help_str.push_str(
+        format!(
+            "\nRun `{} [COMMAND] --help` to see help information for a specific command\n",
+            app.name
+        )
+        .as_str(),
     );
 
     // If there was an error, print the message at the bottom of the help screen
     if let Some(msg) = error_msg {
-        println!("\n{}", msg.red());
+        help_str.push_str(format!("\n{}\n", msg.red()).as_str());
     }
+
+    help_str
 }
 
-pub(crate) fn print_help_command(app: &App, command: &Command, error_msg: Option<String>) {
-    println!("{}\n", command.desc);
+pub(crate) fn format_help_command(
+    app: &App,
+    command_path: &[&Command],
+    error_msg: Option<String>,
+) -> String {
+    let command = *command_path.last().unwrap();
 
-    println!("USAGE:\n{}\n", format_command_usage_str(app, command));
+    let mut help_str = format!("{}\n\n", command.desc);
+
+    help_str.push_str(
+        format!(
+            "USAGE:\n{}\n\n",
+            format_command_usage_str(app, command_path)
+        )
+        .as_str(),
+    );
 
     if !command.args.is_empty() {
-        println!("ARGS:");
+        help_str.push_str("ARGS:\n");
         for arg in &command.args {
-            println!("\t<{}>", arg);
+            help_str.push_str(format!("{}\n", format_arg_desc_str(arg)).as_str());
         }
-        println!();
+        help_str.push('\n');
     }
 
     if !command.options.is_empty() {
-        println!("OPTIONS:");
+        help_str.push_str("OPTIONS:\n");
         for option in &command.options {
             // Print the short alias first, if possible
-            println!("{}", format_option_str(option));
+            help_str.push_str(format!("{}\n", format_option_str(option)).as_str());
+        }
+    }
+
+    if !command.relations.is_empty() {
+        help_str.push_str("\nOPTION GROUPS:\n");
+        for relation in &command.relations {
+            help_str.push_str(format!("{}\n", format_relation_str(relation)).as_str());
+        }
+    }
+
+    help_str.push_str(&format_global_options_str(app));
+
+    if !command.subcommands.is_empty() {
+        help_str.push_str("\nCOMMANDS:\n");
+        for subcommand in &command.subcommands {
+            help_str.push_str(format!("{}\n", format_command_str(subcommand)).as_str());
         }
+
+        help_str.push_str(
+            format!(
+                "\nRun `{} [COMMAND] --help` to see help information for a specific command\n",
+                format_command_path_str(app, command_path)
+            )
+            .as_str(),
+        );
     }
 
     if let Some(msg) = error_msg {
-        println!("\n{}", msg.red());
+        help_str.push_str(format!("\n{}\n", msg.red()).as_str());
+    }
+
+    help_str
+}
+
+// Formats the output that matches a parsing error: the version, or the help
+// menu of the application or command. Help requests are shown without an
+// error message. Errors in the configuration file are not mistakes on the
+// command line, so they are shown without a help menu
+pub(crate) fn format_parse_error(app: &App, error: &ParseError) -> String {
+    if let ParseError::InvalidConfig { .. } = error {
+        return format!("{}\n", error.to_string().red());
+    }
+
+    let command_path = match error.command() {
+        Some(command) => app.lookup_command_path(command),
+        None => vec![],
+    };
+
+    let error_msg = match error {
+        ParseError::VersionRequested => return format_version(app),
+        ParseError::MissingCommand | ParseError::HelpRequested { .. } => None,
+        _ => Some(error.to_string()),
+    };
+
+    if command_path.is_empty() {
+        format_help_app(app, error_msg)
+    } else {
+        format_help_command(app, &command_path, error_msg)
+    }
+}
+
+// Returns the long aliases of every command in the path, e.g. `remote`
+// and `add`
+pub(crate) fn command_path_aliases(command_path: &[&Command]) -> Vec<String> {
+    command_path
+        .iter()
+        .map(|command| command.alias_long.clone())
+        .collect()
+}
+
+// Formats the long aliases of every command in the path, e.g. `remote add`
+pub(crate) fn format_command_path(command_path: &[&Command]) -> String {
+    command_path_aliases(command_path).join(" ")
+}
+
+// Collects the paths of every command and subcommand of the application,
+// parents first. Builtin commands are not included
+pub(crate) fn collect_command_paths(app: &App) -> Vec<Vec<&Command>> {
+    let mut command_paths: Vec<Vec<&Command>> =
+        app.commands.iter().map(|command| vec![command]).collect();
+
+    let mut index = 0;
+    while index < command_paths.len() {
+        let command_path = command_paths[index].clone();
+        let subcommands = command_path.last().unwrap().subcommands.iter();
+
+        // Insert the subcommands right after their parent
+        for (offset, subcommand) in subcommands.enumerate() {
+            let mut subcommand_path = command_path.clone();
+            subcommand_path.push(subcommand);
+            command_paths.insert(index + offset + 1, subcommand_path);
+        }
+
+        index += 1;
     }
+
+    command_paths
 }
 
-fn format_command_usage_str(app: &App, command: &Command) -> String {
+pub(crate) fn format_command_usage_str(app: &App, command_path: &[&Command]) -> String {
+    let command = *command_path.last().unwrap();
     let mut command_str = String::from("\t");
 
-    command_str.push_str(format!("{} {}", app.name, command.alias_long).as_str());
+    command_str.push_str(&format_command_path_str(app, command_path));
 
     if !command.options.is_empty() {
         command_str.push_str(" [OPTIONS]");
     }
 
-    for arg_name in &command.args {
-        command_str.push_str(format!(" <{}>", arg_name).as_str());
+    // Required options are always shown in the usage line
+    let options = command.options.iter().chain(&app.global_options);
+    for option in options.filter(|option| option.required) {
+        command_str.push_str(format!(" {}", option.alias_long).as_str());
+
+        if let Some(argument_name) = &option.arg {
+            command_str.push_str(format!(" <{}>", argument_name).as_str());
+        }
+    }
+
+    if !command.subcommands.is_empty() {
+        command_str.push_str(" [COMMAND]");
+    }
+
+    for arg in &command.args {
+        command_str.push_str(format!(" {}", format_arg_str(arg)).as_str());
     }
 
     command_str
 }
 
+// Formats the application name followed by the long aliases of every
+// command in the path, e.g. `app remote add`
+fn format_command_path_str(app: &App, command_path: &[&Command]) -> String {
+    format!("{} {}", app.name, format_command_path(command_path))
+}
+
+// Required arguments are shown in angle brackets, optional ones in square
+// brackets
+pub(crate) fn format_arg_str(arg: &CommandArg) -> String {
+    let mut arg_str = match arg.min {
+        0 => format!("[{}]", arg.name),
+        _ => format!("<{}>", arg.name),
+    };
+
+    // Variadic arguments are followed by an ellipsis
+    if arg.is_variadic() {
+        arg_str.push_str("...");
+    }
+
+    arg_str
+}
+
+// Formats an argument along with its default value and environment
+// variable, if it has any
+fn format_arg_desc_str(arg: &CommandArg) -> String {
+    let mut details = Vec::<String>::new();
+
+    if !arg.choices.is_empty() {
+        details.push(format_choices_str(&arg.choices));
+    }
+
+    if let Some(default) = &arg.default {
+        details.push(format!("[default: {}]", default));
+    }
+
+    if let Some(var) = &arg.env {
+        details.push(format!("[env: {}]", var));
+    }
+
+    let arg_str = format!("\t{}", format_arg_str(arg));
+    if details.is_empty() {
+        return arg_str;
+    }
+
+    format!("{:<30}{}", arg_str, details.join(" "))
+}
+
 fn format_option_str(option: &CommandOption) -> String {
     let mut option_str = String::from("\t");
 
@@ -90,10 +262,65 @@ fn format_option_str(option: &CommandOption) -> String {
         option_str.push_str(format!(" <{}>", argument_name).as_str());
     }
 
+    // Repeatable options are followed by an ellipsis
+    if option.repeatable {
+        option_str.push_str("...");
+    }
+
+    if !option.choices.is_empty() {
+        option_str.push_str(format!(" {}", format_choices_str(&option.choices)).as_str());
+    }
+
     option_str = format!("{:<30}{}", option_str, &option.description);
+
+    if let Some(default) = &option.default {
+        option_str.push_str(format!(" [default: {}]", default).as_str());
+    }
+
+    if let Some(var) = &option.env {
+        option_str.push_str(format!(" [env: {}]", var).as_str());
+    }
+
     option_str
 }
 
+// Formats a relationship between the options of a command along with a
+// description of what it means
+fn format_relation_str(relation: &OptionRelation) -> String {
+    let (aliases, desc) = match relation {
+        OptionRelation::Conflicts(aliases) => {
+            (aliases.join(", "), String::from("Cannot be used together"))
+        }
+        OptionRelation::Requires(alias, required) => {
+            (alias.clone(), format!("Requires {}", required))
+        }
+        OptionRelation::OneOf(aliases) => {
+            (aliases.join(", "), String::from("At least one is required"))
+        }
+    };
+
+    format!("{:<30}{}", format!("\t{}", aliases), desc)
+}
+
+// Formats the options that are accepted by every command, if there are any
+fn format_global_options_str(app: &App) -> String {
+    if app.global_options.is_empty() {
+        return String::new();
+    }
+
+    let mut options_str = String::from("\nGLOBAL OPTIONS:\n");
+    for option in &app.global_options {
+        options_str.push_str(format!("{}\n", format_option_str(option)).as_str());
+    }
+
+    options_str
+}
+
+// Formats the values an argument or option accepts, e.g. `[debug|info|warn]`
+pub(crate) fn format_choices_str(choices: &[String]) -> String {
+    format!("[{}]", choices.join("|"))
+}
+
 fn format_command_str(command: &Command) -> String {
     let mut command_str = String::from("\t");
 
@@ -107,6 +334,6 @@ fn format_command_str(command: &Command) -> String {
     command_str
 }
 
-pub(crate) fn print_version(app: &App) {
-    println!("{} {}", app.name, app.version);
+pub(crate) fn format_version(app: &App) -> String {
+    format!("{} {}\n", app.name, app.version)
 }
@@ -0,0 +1,68 @@
+use crate::command::Command;
+use crate::help::format_command_path;
+use crate::types::*;
+
+/// Holds a parsed command line: the command to run and what to pass into it.
+///
+/// Returned by [App::try_parse](crate::App::try_parse). Nothing has been run
+/// or printed when an invocation is created, so it can be inspected, logged,
+/// or validated before it is passed to [App::dispatch](crate::App::dispatch)
+/// to run the command function.
+///
+/// # Examples
+///
+/// Parsing the arguments, inspecting them, and running the command:
+///
+/// ```
+/// use climb::*;
+///
+/// fn example_cmd_fn(_: FunctionInput, _: FunctionOptions) -> FunctionResult {
+///     Ok(Some(String::from("done")))
+/// }
+///
+/// let my_app = create_app!()
+///     .name("app")
+///     .command(Command::new("cmd_name", "cmd_desc", example_cmd_fn).arg("file"));
+///
+/// let invocation = my_app
+///     .try_parse(vec![
+///         String::from("app"),
+///         String::from("cmd_name"),
+///         String::from("notes.txt"),
+///     ])
+///     .unwrap();
+///
+/// assert_eq!(invocation.command_name(), "cmd_name");
+/// assert_eq!(invocation.input(), &vec![String::from("notes.txt")]);
+///
+/// let result = my_app.dispatch(invocation);
+/// assert_eq!(result, Ok(Some(String::from("done"))));
+/// ```
+pub struct Invocation<'a> {
+    pub(crate) command_path: Vec<&'a Command>,
+    pub(crate) input: FunctionInput,
+    pub(crate) options: FunctionOptions,
+}
+
+impl<'a> Invocation<'a> {
+    /// Returns the command that will be run
+    pub fn command(&self) -> &'a Command {
+        self.command_path.last().unwrap()
+    }
+
+    /// Returns the long aliases of the command that will be run and its
+    /// parent commands, separated by spaces (e.g. `remote add`)
+    pub fn command_name(&self) -> String {
+        format_command_path(&self.command_path)
+    }
+
+    /// Returns the arguments that will be passed into the command function
+    pub fn input(&self) -> &FunctionInput {
+        &self.input
+    }
+
+    /// Returns the options that will be passed into the command function
+    pub fn options(&self) -> &FunctionOptions {
+        &self.options
+    }
+}
@@ -60,14 +60,25 @@
 
 mod app;
 mod command;
+mod completion;
+mod config;
+mod error;
 mod help;
+mod invocation;
+mod manpage;
+mod markdown;
+mod suggest;
 mod types;
 
 pub use app::App;
 pub use command::*;
+pub use completion::{CompletionContext, CompletionFunction, Shell, ValueHint};
+pub use error::ParseError;
+pub use invocation::Invocation;
 pub use types::*;
 
 #[cfg(test)]
+#[allow(clippy::get_first)]
 mod tests {
     use crate::*;
 
@@ -93,6 +104,11 @@ mod tests {
         Ok(Some(result.to_string()))
     }
 
+    // Builds the arguments of a command line, starting with the application name
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
     #[test]
     fn command_new() {
         let command = Command::new("command_alias", "command_desc", add_fn);
@@ -211,4 +227,1643 @@ mod tests {
 
         assert_eq!(None, res.unwrap());
     }
+
+    #[test]
+    fn app_functionality_subcommand() {
+        let math_cmd = Command::new("math", "Do some math", add_fn)
+            .subcommand(
+                Command::new("add", "Add two numbers", add_fn)
+                    .arg("a")
+                    .arg("b"),
+            )
+            .subcommand(
+                Command::new("div", "Divide two numbers", div_fn)
+                    .arg("a")
+                    .arg("b"),
+            );
+
+        let app = create_app!().name("app_name").command(math_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "math", "div", "10", "4"]));
+
+        assert_eq!("2.5", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_subcommand_parent() {
+        let math_cmd = Command::new("math", "Do some math", add_fn)
+            .arg("a")
+            .arg("b")
+            .subcommand(
+                Command::new("div", "Divide two numbers", div_fn)
+                    .arg("a")
+                    .arg("b"),
+            );
+
+        let app = create_app!().name("app_name").command(math_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "math", "10", "4"]));
+
+        assert_eq!("14", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_subcommand_alias_as_parent_arg() {
+        let math_cmd = Command::new("math", "Do some math", add_fn)
+            .arg("a")
+            .arg("b")
+            .subcommand(
+                Command::new("div", "Divide two numbers", div_fn)
+                    .arg("a")
+                    .arg("b"),
+            );
+
+        let app = create_app!().name("app_name").command(math_cmd);
+
+        // The alias of a subcommand calls the subcommand
+        let res = app.try_run_custom(argv(&["app_name", "math", "div", "4"]));
+        assert_eq!(
+            ParseError::WrongArgumentCount {
+                command: argv(&["math", "div"]),
+                min: 2,
+                max: Some(2),
+                actual: 1
+            },
+            res.unwrap_err()
+        );
+
+        // Unless it comes after `--`
+        let res = app.try_parse(argv(&["app_name", "math", "--", "div", "4"]));
+        assert_eq!(&argv(&["div", "4"]), res.unwrap().input());
+    }
+
+    #[test]
+    fn app_functionality_string_arg_names() {
+        let names = argv(&["a", "b"]);
+        let add_cmd = Command::new("add", "Add two numbers", add_fn)
+            .arg(&names[0])
+            .arg(names[1].clone());
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", "1", "2"]));
+        assert_eq!("3", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_closure() {
+        let offset = 100;
+        let add_cmd = Command::new("add", "Add a number to the offset", move |input, _| {
+            let num: i32 = input.first().unwrap().parse().unwrap();
+            Ok(Some((num + offset).to_string()))
+        })
+        .arg("a");
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", "5"]));
+
+        assert_eq!("105", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_typed() {
+        let add_cmd = Command::new("add", "Add two numbers", |input, options| {
+            let num_a: i32 = input.value(0).unwrap();
+            let num_b: i32 = input.value(1).unwrap();
+            let times: i32 = options.value("--times").unwrap_or(1);
+
+            Ok(Some(((num_a + num_b) * times).to_string()))
+        })
+        .arg_typed::<i32>("a")
+        .arg_typed::<i32>("b")
+        .option(CommandOption::new("times", "Multiply the result").arg_typed::<i32>("times"));
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", "--times", "3", "1", "2"]));
+
+        assert_eq!("9", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_typed_invalid_input() {
+        let add_cmd = Command::new("add", "Add two numbers", |_, _| panic!("not called"))
+            .arg_typed::<i32>("a")
+            .arg_typed::<i32>("b");
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", "1", "two"]));
+
+        assert_eq!(None, res.unwrap());
+    }
+
+    #[test]
+    fn app_functionality_optional_arg() {
+        let add_cmd = Command::new("add", "Add two numbers", add_fn)
+            .arg("a")
+            .optional_arg("b", "1");
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", "9"]));
+        assert_eq!("10", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "add", "9", "5"]));
+        assert_eq!("14", res.unwrap().unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    fn command_required_arg_after_optional() {
+        let _ = Command::new("add", "Add two numbers", add_fn)
+            .optional_arg("a", "1")
+            .arg("b");
+    }
+
+    fn join_fn(input: FunctionInput, _: FunctionOptions) -> FunctionResult {
+        Ok(Some(input.join(" ")))
+    }
+
+    #[test]
+    fn app_functionality_variadic_arg() {
+        let join_cmd = Command::new("join", "Join some words", join_fn)
+            .arg("first")
+            .variadic_arg("rest", 1, Some(3));
+
+        let app = create_app!().name("app_name").command(join_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "join", "a", "b", "c"]));
+        assert_eq!("a b c", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "join", "a"]));
+        assert_eq!(None, res.unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "join", "a", "b", "c", "d", "e"]));
+        assert_eq!(None, res.unwrap());
+    }
+
+    #[test]
+    fn app_functionality_passthrough() {
+        let join_cmd = Command::new("join", "Join some words", join_fn)
+            .variadic_arg("words", 0, None)
+            .option(CommandOption::new("round", "round the result"));
+
+        let app = create_app!().name("app_name").command(join_cmd);
+
+        let res = app.run_custom(argv(&[
+            "app_name", "join", "--round", "a", "--", "--round", "-x",
+        ]));
+        assert_eq!("a --round -x", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_passthrough_arity() {
+        let run_cmd = Command::new("run", "Run a program", join_fn)
+            .arg("program")
+            .variadic_arg("args", 0, None);
+        let open_cmd = Command::new("open", "Open a file", join_fn).arg("file");
+
+        let app = create_app!()
+            .name("app_name")
+            .command(run_cmd)
+            .command(open_cmd);
+
+        // The values after `--` fill the remaining arguments
+        let res = app.run_custom(argv(&["app_name", "run", "--", "ls", "-la", "--", "/"]));
+        assert_eq!("ls -la -- /", res.unwrap().unwrap());
+
+        // And count towards the amount of arguments
+        let res = app.try_run_custom(argv(&["app_name", "open", "a", "--", "-b"]));
+        assert_eq!(
+            ParseError::WrongArgumentCount {
+                command: argv(&["open"]),
+                min: 1,
+                max: Some(1),
+                actual: 2
+            },
+            res.unwrap_err()
+        );
+    }
+
+    fn options_fn(_: FunctionInput, options: FunctionOptions) -> FunctionResult {
+        let options: Vec<String> = options
+            .iter()
+            .map(|FunctionOption(alias, value)| match value {
+                Some(value) => format!("{}={}", alias, value),
+                None => alias.clone(),
+            })
+            .collect();
+
+        Ok(Some(options.join(" ")))
+    }
+
+    #[test]
+    fn app_functionality_gnu_options() {
+        let cmd = Command::new("opts", "Print the options", options_fn)
+            .option(CommandOption::new("recursive", "recursive").alias("r"))
+            .option(CommandOption::new("force", "force").alias("f"))
+            .option(
+                CommandOption::new("output", "output file")
+                    .alias("o")
+                    .arg("file"),
+            );
+
+        let app = create_app!().name("app_name").command(cmd);
+
+        let res = app.run_custom(argv(&["app_name", "opts", "-rfofile.txt"]));
+        assert_eq!(
+            "--recursive --force --output=file.txt",
+            res.unwrap().unwrap()
+        );
+
+        let res = app.run_custom(argv(&["app_name", "opts", "--output=file.txt"]));
+        assert_eq!("--output=file.txt", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "opts", "--force=yes"]));
+        assert_eq!(None, res.unwrap());
+    }
+
+    #[test]
+    fn app_functionality_repeated_options() {
+        let cmd = Command::new("opts", "Count the options", |_, options| {
+            let includes: Vec<String> = options.values("--include");
+            Ok(Some(format!(
+                "{} {}",
+                options.count("--verbose"),
+                includes.join(",")
+            )))
+        })
+        .option(
+            CommandOption::new("verbose", "verbosity")
+                .alias("v")
+                .repeatable(),
+        )
+        .option(
+            CommandOption::new("include", "include")
+                .arg("dir")
+                .repeatable(),
+        )
+        .option(CommandOption::new("force", "force").alias("f"));
+
+        let app = create_app!().name("app_name").command(cmd);
+
+        let res = app.run_custom(argv(&[
+            "app_name",
+            "opts",
+            "-vvv",
+            "--include",
+            "a",
+            "-v",
+            "--include=b",
+        ]));
+        assert_eq!("4 a,b", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "opts", "-f", "--force"]));
+        assert_eq!(None, res.unwrap());
+    }
+
+    #[test]
+    fn app_functionality_required_option() {
+        let cmd = Command::new("opts", "Print the options", options_fn).option(
+            CommandOption::new("region", "region")
+                .arg("region")
+                .required(),
+        );
+
+        let app = create_app!().name("app_name").command(cmd);
+
+        let res = app.run_custom(argv(&["app_name", "opts", "--region", "eu"]));
+        assert_eq!("--region=eu", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "opts"]));
+        assert_eq!(None, res.unwrap());
+    }
+
+    #[test]
+    fn app_functionality_negative_numbers() {
+        let add_cmd = Command::new("add", "Add two numbers", add_fn)
+            .arg("a")
+            .arg("b")
+            .option(CommandOption::new("offset", "offset").arg_typed::<i32>("offset"));
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", "-5", "3", "--offset", "-10"]));
+        assert_eq!("-2", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_functionality_hyphen_values() {
+        let cmd = Command::new("join", "Join some words", join_fn)
+            .variadic_arg("words", 0, None)
+            .allow_hyphen_values()
+            .option(
+                CommandOption::new("flag", "flag")
+                    .arg("flag")
+                    .allow_hyphen_values(),
+            );
+
+        let app = create_app!().name("app_name").command(cmd);
+
+        let res = app.run_custom(argv(&["app_name", "join", "ls", "-la", "--flag", "-O2"]));
+        assert_eq!("ls -la", res.unwrap().unwrap());
+
+        // Known options are still checked
+        let cmd = Command::new("join", "Join some words", join_fn)
+            .variadic_arg("words", 0, None)
+            .allow_hyphen_values()
+            .option(CommandOption::new("round", "round the result").alias("r"));
+
+        let app = create_app!().name("app_name").command(cmd);
+
+        let res = app.try_run_custom(argv(&["app_name", "join", "--round=yes"]));
+        assert_eq!(
+            ParseError::UnexpectedOptionArgument {
+                token: "--round".to_string(),
+                command: argv(&["join"])
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "join", "-rx"]));
+        assert_eq!(
+            ParseError::UnknownOption {
+                token: "-x".to_string(),
+                command: Some(argv(&["join"])),
+                suggestions: vec![]
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.run_custom(argv(&["app_name", "join", "-xr", "--rounds"]));
+        assert_eq!("-xr --rounds", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_parse_errors() {
+        let add_cmd = Command::new("add", "Add two numbers", add_fn)
+            .arg_typed::<i32>("a")
+            .arg("b");
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.try_run_custom(argv(&["app_name", "sub"]));
+        assert_eq!(
+            ParseError::UnknownCommand {
+                token: "sub".to_string(),
+                command: None,
+                suggestions: vec![]
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "add", "1"]));
+        assert_eq!(
+            ParseError::WrongArgumentCount {
+                command: argv(&["add"]),
+                min: 2,
+                max: Some(2),
+                actual: 1
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "add", "x", "1"]));
+        assert!(matches!(
+            res.unwrap_err(),
+            ParseError::InvalidValue { token, .. } if token == "x"
+        ));
+
+        let res = app.try_run_custom(argv(&["app_name", "add", "--help"]));
+        assert_eq!(
+            ParseError::HelpRequested {
+                command: Some(argv(&["add"]))
+            },
+            res.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn app_try_parse_and_dispatch() {
+        let math_cmd = Command::new("math", "Do some math", add_fn).subcommand(
+            Command::new("add", "Add two numbers", add_fn)
+                .arg("a")
+                .arg("b"),
+        );
+
+        let app = create_app!().name("app_name").command(math_cmd);
+
+        let invocation = app
+            .try_parse(argv(&["app_name", "math", "add", "1", "2"]))
+            .unwrap();
+
+        assert_eq!("math add", invocation.command_name());
+        assert_eq!(&vec!["1".to_string(), "2".to_string()], invocation.input());
+        assert!(invocation.options().is_empty());
+        assert_eq!("3", app.dispatch(invocation).unwrap().unwrap());
+    }
+
+    #[test]
+    fn app_exit_codes() {
+        let add_cmd = Command::new("add", "Add two numbers", add_fn)
+            .arg("a")
+            .arg("b");
+        let fail_cmd = Command::new("fail", "Always fails", |_, _| Err("failed".to_string()));
+
+        let app = create_app!()
+            .name("app_name")
+            .command(add_cmd)
+            .command(fail_cmd);
+
+        assert_eq!(
+            0,
+            app.run_for_exit_code(argv(&["app_name", "add", "1", "2"]))
+        );
+        assert_eq!(0, app.run_for_exit_code(argv(&["app_name", "--version"])));
+        assert_eq!(0, app.run_for_exit_code(argv(&["app_name", "add", "-h"])));
+        assert_eq!(1, app.run_for_exit_code(argv(&["app_name", "fail"])));
+        assert_eq!(2, app.run_for_exit_code(argv(&["app_name", "add", "1"])));
+        assert_eq!(2, app.run_for_exit_code(argv(&["app_name", "sub"])));
+    }
+
+    #[test]
+    fn app_functionality_env() {
+        std::env::set_var("CLIMB_TEST_ENV_B", "20");
+        std::env::set_var("CLIMB_TEST_ENV_ROUND", "true");
+        std::env::remove_var("CLIMB_TEST_ENV_MISSING");
+
+        let div_cmd = Command::new("div", "Divide two numbers", div_fn)
+            .arg("a")
+            .arg(CommandArg::new("b").env("CLIMB_TEST_ENV_B"))
+            .option(CommandOption::new("round", "round the result").env("CLIMB_TEST_ENV_ROUND"));
+        let add_cmd = Command::new("add", "Add two numbers", add_fn)
+            .arg("a")
+            .arg(CommandArg::new("b").env("CLIMB_TEST_ENV_MISSING"));
+
+        let app = create_app!()
+            .name("app_name")
+            .command(div_cmd)
+            .command(add_cmd);
+
+        let res_env = app.run_custom(argv(&["app_name", "div", "50"]));
+        let res_arg = app.run_custom(argv(&["app_name", "div", "50", "10"]));
+        let res_missing = app.run_custom(argv(&["app_name", "add", "50"]));
+
+        std::env::remove_var("CLIMB_TEST_ENV_B");
+        std::env::remove_var("CLIMB_TEST_ENV_ROUND");
+
+        assert_eq!("3", res_env.unwrap().unwrap());
+        assert_eq!("5", res_arg.unwrap().unwrap());
+        assert_eq!(None, res_missing.unwrap());
+    }
+
+    #[test]
+    fn app_functionality_env_arg_kinds() {
+        std::env::set_var("CLIMB_TEST_ENV_KINDS_PORT", "8080");
+        std::env::set_var("CLIMB_TEST_ENV_KINDS_DEST", "out");
+        std::env::set_var("CLIMB_TEST_ENV_KINDS_FILES", "a.txt");
+
+        let serve_cmd = Command::new("serve", "Start the server", join_fn).arg(
+            CommandArg::new("port")
+                .typed::<u16>()
+                .env("CLIMB_TEST_ENV_KINDS_PORT"),
+        );
+        let copy_cmd = Command::new("copy", "Copy files into a folder", join_fn)
+            .arg(
+                CommandArg::new("dest")
+                    .optional(".")
+                    .env("CLIMB_TEST_ENV_KINDS_DEST"),
+            )
+            .arg(
+                CommandArg::new("files")
+                    .variadic(0, None)
+                    .env("CLIMB_TEST_ENV_KINDS_FILES"),
+            );
+
+        let app = create_app!()
+            .name("app_name")
+            .command(serve_cmd)
+            .command(copy_cmd);
+
+        let res_typed = app.run_custom(argv(&["app_name", "serve"]));
+        let res_optional = app.run_custom(argv(&["app_name", "copy"]));
+        let res_variadic = app.run_custom(argv(&["app_name", "copy", "dir"]));
+        let res_arg = app.run_custom(argv(&["app_name", "copy", "dir", "b.txt", "c.txt"]));
+
+        std::env::set_var("CLIMB_TEST_ENV_KINDS_PORT", "http");
+        let res_invalid = app.try_run_custom(argv(&["app_name", "serve"]));
+
+        std::env::remove_var("CLIMB_TEST_ENV_KINDS_PORT");
+        std::env::remove_var("CLIMB_TEST_ENV_KINDS_DEST");
+        std::env::remove_var("CLIMB_TEST_ENV_KINDS_FILES");
+
+        assert_eq!("8080", res_typed.unwrap().unwrap());
+        assert_eq!("out a.txt", res_optional.unwrap().unwrap());
+        assert_eq!("dir a.txt", res_variadic.unwrap().unwrap());
+        assert_eq!("dir b.txt c.txt", res_arg.unwrap().unwrap());
+        assert!(matches!(
+            res_invalid.unwrap_err(),
+            ParseError::InvalidValue { .. }
+        ));
+    }
+
+    #[test]
+    fn app_functionality_config_file() {
+        let path = std::env::temp_dir().join(format!(
+            "climb_test_config_file_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "# Comment\nregion = top\n\n[opts]\nregion = \"eu\"\nforce = true\n",
+        )
+        .unwrap();
+        std::env::set_var("CLIMB_TEST_CONFIG_ZONE", "b");
+
+        let cmd = Command::new("opts", "Print the options", options_fn)
+            .option(CommandOption::new("region", "region").arg("region"))
+            .option(CommandOption::new("force", "force"))
+            .option(
+                CommandOption::new("zone", "zone")
+                    .arg("zone")
+                    .env("CLIMB_TEST_CONFIG_ZONE")
+                    .default_value("a"),
+            )
+            .option(
+                CommandOption::new("tier", "tier")
+                    .arg("tier")
+                    .default_value("free"),
+            );
+
+        let app = create_app!()
+            .name("app_name")
+            .command(cmd)
+            .config_file(path.to_str().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "opts", "--region=us"]));
+        assert_eq!(
+            "--region=us --force --zone=b --tier=free",
+            res.unwrap().unwrap()
+        );
+
+        let res = app.run_custom(argv(&["app_name", "opts"]));
+        assert_eq!(
+            "--region=eu --force --zone=b --tier=free",
+            res.unwrap().unwrap()
+        );
+
+        std::fs::write(&path, "[opts\n").unwrap();
+        let res = app.try_run_custom(argv(&["app_name", "opts"]));
+
+        std::fs::remove_file(&path).unwrap();
+        std::env::remove_var("CLIMB_TEST_CONFIG_ZONE");
+
+        assert!(matches!(
+            res.unwrap_err(),
+            ParseError::InvalidConfig { line: Some(1), .. }
+        ));
+    }
+
+    #[test]
+    fn app_functionality_config_file_broken() {
+        let path = std::env::temp_dir().join(format!(
+            "climb_test_config_file_broken_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, "[opts\n").unwrap();
+
+        let cmd = Command::new("opts", "Print the options", options_fn).option(
+            CommandOption::new("region", "region")
+                .arg("region")
+                .choices(["eu", "us"]),
+        );
+
+        let app = create_app!()
+            .name("app_name")
+            .command(cmd)
+            .config_file(path.to_str().unwrap());
+
+        // The configuration file is not read for help, builtins, or when
+        // every option was passed in
+        let res_help = app.try_run_custom(argv(&["app_name", "opts", "--help"]));
+        let res_app_help = app.try_run_custom(argv(&["app_name", "--help"]));
+        let res_completions = app.try_run_custom(argv(&["app_name", "completions", "bash"]));
+        let res_complete = app.try_run_custom(argv(&[
+            "app_name",
+            "__complete",
+            "--",
+            "opts",
+            "--region",
+            "",
+        ]));
+        let res_passed = app.try_run_custom(argv(&["app_name", "opts", "--region=eu"]));
+        let res_missing = app.try_run_custom(argv(&["app_name", "opts"]));
+        let exit_code = app.run_for_exit_code(argv(&["app_name", "opts"]));
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            ParseError::HelpRequested {
+                command: Some(argv(&["opts"]))
+            },
+            res_help.unwrap_err()
+        );
+        assert_eq!(
+            ParseError::HelpRequested { command: None },
+            res_app_help.unwrap_err()
+        );
+        assert!(res_completions.is_ok());
+        assert_eq!(Ok(Ok(Some("eu\nus".to_string()))), res_complete);
+        assert_eq!(Ok(Ok(Some("--region=eu".to_string()))), res_passed);
+        let error = res_missing.unwrap_err();
+        assert!(matches!(
+            error,
+            ParseError::InvalidConfig { line: Some(1), .. }
+        ));
+
+        // The error is not a mistake on the command line, so it is shown
+        // without a help menu and has its own exit code
+        let output = crate::help::format_parse_error(&app, &error);
+        assert!(!output.contains("USAGE:"));
+        assert!(output.contains(&error.to_string()));
+        assert_eq!(78, exit_code);
+    }
+
+    #[test]
+    fn app_completions() {
+        let remote_cmd = Command::new("remote", "Manage remotes", add_fn)
+            .alias("r")
+            .subcommand(
+                Command::new("add", "Add a remote", add_fn)
+                    .option(CommandOption::new("output", "Output file").arg("file")),
+            );
+
+        let app = create_app!().name("app_name").command(remote_cmd);
+
+        let mut script = Vec::new();
+        app.generate_completions(Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("\"app_name:remote\"|\"app_name:r\") cmd_path=\"app_name remote\""));
+        assert!(script.contains("complete -F _app_name app_name"));
+
+        let res = app.run_custom(argv(&["app_name", "completions", "fish"]));
+        let script = res.unwrap().unwrap();
+        assert!(script.contains("-l output -r -a '(__app_name_values)' -d 'Output file'"));
+
+        let res = app.run_custom(argv(&["app_name", "completions", "powershell"]));
+        assert_eq!(None, res.unwrap());
+
+        // Errors in builtin commands show the help menu of the command
+        let error = app
+            .try_run_custom(argv(&["app_name", "completions", "powershell"]))
+            .unwrap_err();
+        let help = crate::help::format_parse_error(&app, &error);
+        assert!(help.contains("Generate a shell completion script"));
+        assert!(help.contains("app_name completions"));
+    }
+
+    #[test]
+    fn app_dynamic_completions_arg_kinds() {
+        let dir =
+            std::env::temp_dir().join(format!("climb_test_completions_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), "").unwrap();
+
+        let cp_cmd = Command::new("cp", "Copy files", join_fn).arg(
+            CommandArg::new("files")
+                .variadic(1, None)
+                .hint(ValueHint::File),
+        );
+        let log_cmd = Command::new("log", "Print the log", join_fn).arg(
+            CommandArg::new("level")
+                .optional("info")
+                .choices(["info", "warn"]),
+        );
+        let echo_cmd =
+            Command::new("echo", "Print the words", join_fn).variadic_arg("words", 0, None);
+
+        let app = create_app!()
+            .name("app_name")
+            .command(cp_cmd)
+            .command(log_cmd)
+            .command(echo_cmd);
+
+        let current = format!("{}/", dir.display());
+        let res_files =
+            app.run_custom(argv(&["app_name", "__complete", "--", "cp", "b", &current]));
+        let res_level = app.run_custom(argv(&["app_name", "__complete", "--", "log", "w"]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(Some(format!("{}a.txt", current)), res_files.unwrap());
+        assert_eq!(Some("warn".to_string()), res_level.unwrap());
+
+        // Fish only completes file names for arguments without a hint
+        let res = app.run_custom(argv(&["app_name", "completions", "fish"]));
+        let script = res.unwrap().unwrap();
+        assert!(!script.contains("complete -c app_name -f"));
+        assert!(script.contains("-n 'test (__app_name_cmd_path) = \"app_name\"' -f"));
+        assert!(script.contains("-n 'test (__app_name_cmd_path) = \"app_name cp\"' -f"));
+        assert!(script.contains("-n 'test (__app_name_cmd_path) = \"app_name log\"' -f"));
+        assert!(!script.contains("-n 'test (__app_name_cmd_path) = \"app_name echo\"' -f"));
+    }
+
+    #[test]
+    fn app_dynamic_completions() {
+        let deploy_cmd = Command::new("deploy", "Deploy the app", add_fn)
+            .arg(CommandArg::new("env").hint(ValueHint::Choices(argv(&["staging", "production"]))))
+            .arg(CommandArg::new("branch").hint(ValueHint::Custom(Box::new(
+                |context: &CompletionContext| {
+                    // Branches depend on the environment that was typed
+                    vec![format!("{}-main", context.input()[0])]
+                },
+            ))))
+            .option(
+                CommandOption::new("format", "Output format")
+                    .alias("f")
+                    .arg("format")
+                    .hint(ValueHint::Choices(argv(&["json", "yaml"]))),
+            );
+
+        let app = create_app!().name("app_name").command(deploy_cmd);
+
+        let complete = |words: &[&str]| {
+            let mut args = argv(&["app_name", "__complete", "--"]);
+            args.extend(words.iter().map(|word| word.to_string()));
+            app.run_custom(args).unwrap()
+        };
+
+        assert_eq!(Some("staging".to_string()), complete(&["deploy", "st"]));
+        assert_eq!(
+            Some("production-main".to_string()),
+            complete(&["deploy", "--format", "json", "production", ""])
+        );
+        assert_eq!(
+            Some("json\nyaml".to_string()),
+            complete(&["deploy", "-f", ""])
+        );
+        assert_eq!(
+            Some("--format=yaml".to_string()),
+            complete(&["deploy", "--format=y"])
+        );
+        assert_eq!(None, complete(&["deploy", "--fo"]));
+        assert_eq!(None, complete(&["deploy", "staging", "main", ""]));
+
+        // Bash splits `--format=` into `--format` and `=`, and only replaces
+        // the value after the `=`
+        assert_eq!(
+            Some("json\nyaml".to_string()),
+            complete(&["deploy", "--format", "="])
+        );
+        assert_eq!(
+            Some("yaml".to_string()),
+            complete(&["deploy", "--format", "=", "y"])
+        );
+        assert_eq!(
+            Some("production-main".to_string()),
+            complete(&["deploy", "--format", "=", "json", "production", ""])
+        );
+    }
+
+    #[test]
+    fn app_dynamic_completions_hyphen_values() {
+        let move_cmd = Command::new("move", "Move the cursor", add_fn)
+            .arg("by")
+            .arg(CommandArg::new("unit").choices(["px", "em"]));
+        let exec_cmd = Command::new("exec", "Run a program", add_fn)
+            .arg("flags")
+            .arg(CommandArg::new("mode").choices(["fast", "slow"]))
+            .allow_hyphen_values()
+            .option(CommandOption::new("quiet", "Print nothing").alias("q"));
+
+        let app = create_app!()
+            .name("app_name")
+            .command(move_cmd)
+            .command(exec_cmd);
+
+        let complete = |words: &[&str]| {
+            let mut args = argv(&["app_name", "__complete", "--"]);
+            args.extend(words.iter().map(|word| word.to_string()));
+            app.run_custom(args).unwrap()
+        };
+
+        // Negative numbers and unknown options are arguments, like in the parser
+        assert_eq!(Some("px\nem".to_string()), complete(&["move", "-5", ""]));
+        assert_eq!(
+            Some("fast\nslow".to_string()),
+            complete(&["exec", "-la", ""])
+        );
+        assert_eq!(
+            Some("fast\nslow".to_string()),
+            complete(&["exec", "-q", "-la", ""])
+        );
+    }
+
+    #[test]
+    fn app_manpages() {
+        let remote_cmd = Command::new("remote", "Manage remotes", add_fn)
+            .alias("r")
+            .subcommand(
+                Command::new("add", "Add a remote", add_fn)
+                    .arg("name")
+                    .option(CommandOption::new("output", "Output file").arg("file")),
+            );
+
+        let app = create_app!()
+            .name("app_name")
+            .desc("Does things")
+            .version("1.2.3")
+            .command(remote_cmd);
+
+        let page = app.render_manpage();
+        assert!(page.starts_with(".TH \"APP_NAME\" 1 \"\" \"app_name 1.2.3\""));
+        assert!(page.contains(".SH NAME\napp_name \\- Does things\n"));
+        assert!(page.contains(".SS \"remote add\"\nAdd a remote\n"));
+        assert!(page.contains("Usage: app_name remote add [OPTIONS] <NAME>\n"));
+        assert!(page.contains("\\fB\\-\\-output\\fR \\fI<FILE>\\fR\nOutput file\n"));
+
+        let pages = app.render_command_manpages();
+        let names: Vec<&str> = pages.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["app_name-remote.1", "app_name-remote-add.1"], names);
+        assert!(pages[0]
+            .1
+            .contains(".SH COMMANDS\n.TP\n\\fBadd\\fR\nAdd a remote\n"));
+        assert!(pages[1].1.contains(".SH ARGUMENTS\n.TP\n\\fI<NAME>\\fR\n"));
+    }
+
+    #[test]
+    fn app_markdown() {
+        let remote_cmd = Command::new("remote", "Manage remotes", add_fn)
+            .alias("r")
+            .subcommand(
+                Command::new("add", "Add a remote", add_fn)
+                    .arg(CommandArg::new("name").env("REMOTE_NAME"))
+                    .option(
+                        CommandOption::new("output", "Output file")
+                            .alias("o")
+                            .arg("file")
+                            .default_value("out.txt"),
+                    ),
+            );
+
+        let app = create_app!()
+            .name("app_name")
+            .desc("Does things")
+            .version("1.2.3")
+            .command(remote_cmd);
+
+        let doc = app.render_markdown();
+        assert!(doc.starts_with("# app_name\n\nDoes things\n\nVersion: `1.2.3`\n\n"));
+        assert!(doc.contains("| `remote` | `r` | Manage remotes |\n"));
+        assert!(doc.contains("## app_name remote add\n\nAdd a remote\n\n"));
+        assert!(doc.contains("```text\napp_name remote add [OPTIONS] <NAME>\n```\n"));
+        assert!(doc.contains("| `<NAME>` |  | `REMOTE_NAME` |\n"));
+        assert!(doc.contains("### Commands\n\n| Command | Alias | Description |\n"));
+        assert!(doc.ends_with("| `-o`, `--output <FILE>` | Output file | `out.txt` |  |\n"));
+    }
+
+    #[test]
+    fn app_suggestions() {
+        let status_cmd = Command::new("status", "Show the status", add_fn)
+            .alias("s")
+            .option(CommandOption::new("verbose", "Print more").alias("V"));
+        let push_cmd = Command::new("push", "Push changes", add_fn);
+        let pull_cmd = Command::new("pull", "Pull changes", add_fn);
+
+        let app = create_app!()
+            .name("app_name")
+            .command(status_cmd)
+            .command(push_cmd)
+            .command(pull_cmd);
+
+        let res = app.try_run_custom(argv(&["app_name", "stauts"]));
+        assert_eq!(
+            ParseError::UnknownCommand {
+                token: "stauts".to_string(),
+                command: None,
+                suggestions: vec!["status".to_string()]
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "pusl"]));
+        assert_eq!(
+            "The given command does not exist: `pusl` (did you mean `push` or `pull`?)",
+            res.unwrap_err().to_string()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "status", "--verbos"]));
+        assert_eq!(
+            ParseError::UnknownOption {
+                token: "--verbos".to_string(),
+                command: Some(argv(&["status"])),
+                suggestions: vec!["--verbose".to_string()]
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "--hlep"]));
+        assert_eq!(
+            "The given option does not exist: `--hlep` (did you mean `--help`?)",
+            res.unwrap_err().to_string()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "deploy"]));
+        assert_eq!(
+            "The given command does not exist: `deploy`",
+            res.unwrap_err().to_string()
+        );
+
+        // Subcommands are suggested for commands that take no arguments
+        let remote_cmd = Command::new("remote", "Manage remotes", add_fn)
+            .subcommand(Command::new("add", "Add a remote", add_fn).arg("name"));
+        let app = create_app!().name("app_name").command(remote_cmd);
+
+        let error = app
+            .try_run_custom(argv(&["app_name", "remote", "ad", "n"]))
+            .unwrap_err();
+        assert_eq!(
+            ParseError::UnknownCommand {
+                token: "ad".to_string(),
+                command: Some(argv(&["remote"])),
+                suggestions: vec!["add".to_string()]
+            },
+            error
+        );
+
+        let help = crate::help::format_parse_error(&app, &error);
+        assert!(help.starts_with("Manage remotes\n"));
+        assert!(help.contains("(did you mean `add`?)"));
+    }
+
+    #[test]
+    fn app_infer_prefixes() {
+        let status_cmd = Command::new("status", "Show the status", options_fn)
+            .option(CommandOption::new("verbose", "Print more"))
+            .option(CommandOption::new("version-info", "Print the version"))
+            .option(CommandOption::new("format", "Output format").arg("format"));
+        let stash_cmd = Command::new("stash", "Stash changes", options_fn)
+            .subcommand(Command::new("pop", "Apply stashed changes", options_fn));
+
+        let app = create_app!()
+            .name("app_name")
+            .infer_prefixes()
+            .command(status_cmd)
+            .command(stash_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "stat", "--verb", "--form=json"]));
+        assert_eq!("--verbose --format=json", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "stas", "p"]));
+        assert_eq!("", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "st"]));
+        assert_eq!(
+            ParseError::AmbiguousCommand {
+                token: "st".to_string(),
+                command: None,
+                candidates: vec!["status".to_string(), "stash".to_string()]
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "status", "--ver"]));
+        assert_eq!(
+            "The given option is ambiguous: `--ver` (could be `--verbose` or `--version-info`)",
+            res.unwrap_err().to_string()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "--vers"]));
+        assert_eq!(ParseError::VersionRequested, res.unwrap_err());
+
+        // Errors hold the full aliases of the commands, so the help menu of
+        // the abbreviated command is shown
+        let error = app
+            .try_run_custom(argv(&["app_name", "stas", "p", "--x"]))
+            .unwrap_err();
+        assert_eq!(Some(argv(&["stash", "pop"]).as_slice()), error.command());
+        let help = crate::help::format_parse_error(&app, &error);
+        assert!(help.starts_with("Apply stashed changes"));
+
+        // Subcommands of a command that takes arguments need their full alias
+        let remote_cmd = Command::new("remote", "Show a remote", options_fn)
+            .optional_arg("name", "origin")
+            .subcommand(Command::new("add", "Add a remote", options_fn))
+            .subcommand(Command::new("rename", "Rename a remote", options_fn));
+        let app = create_app!()
+            .name("app_name")
+            .infer_prefixes()
+            .command(remote_cmd);
+
+        let res = app.try_parse(argv(&["app_name", "remote", "a"]));
+        assert_eq!("remote", res.unwrap().command_name());
+
+        let res = app.try_parse(argv(&["app_name", "remote", "re"]));
+        assert_eq!(&argv(&["re"]), res.unwrap().input());
+
+        let res = app.try_parse(argv(&["app_name", "remote", "add"]));
+        assert_eq!("remote add", res.unwrap().command_name());
+
+        // Prefixes are not accepted unless the app opts in
+        let app = create_app!().name("app_name").command(Command::new(
+            "status",
+            "Show the status",
+            options_fn,
+        ));
+        let res = app.try_run_custom(argv(&["app_name", "stat"]));
+        assert!(matches!(
+            res.unwrap_err(),
+            ParseError::UnknownCommand { .. }
+        ));
+    }
+
+    #[test]
+    fn app_global_options_required_builtins() {
+        let app = create_app!()
+            .name("app_name")
+            .global_option(
+                CommandOption::new("token", "The API token")
+                    .arg("token")
+                    .choices(["dev", "prod"])
+                    .required(),
+            )
+            .command(Command::new("deploy", "Deploy the app", options_fn));
+
+        // Builtin commands don't need the options of the application
+        let res = app.run_custom(argv(&["app_name", "completions", "bash"]));
+        assert!(res
+            .unwrap()
+            .unwrap()
+            .contains("complete -F _app_name app_name"));
+
+        let res = app.run_custom(argv(&[
+            "app_name",
+            "__complete",
+            "--",
+            "deploy",
+            "--token",
+            "d",
+        ]));
+        assert_eq!(Some("dev".to_string()), res.unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "deploy"]));
+        assert_eq!(
+            ParseError::MissingRequiredOption {
+                token: "--token".to_string(),
+                command: argv(&["deploy"])
+            },
+            res.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn app_global_options() {
+        let deploy_cmd = Command::new("deploy", "Deploy the app", options_fn)
+            .option(CommandOption::new("force", "Skip checks"))
+            .subcommand(Command::new("status", "Show the deploy status", options_fn));
+
+        let app = create_app!()
+            .name("app_name")
+            .global_option(CommandOption::new("verbose", "Print more output").alias("V"))
+            .global_option(CommandOption::new("output", "Output format").arg("format"))
+            .command(deploy_cmd);
+
+        let res = app.run_custom(argv(&[
+            "app_name",
+            "deploy",
+            "--force",
+            "-V",
+            "--output=json",
+        ]));
+        assert_eq!("--force --verbose --output=json", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "deploy", "status", "--verbose"]));
+        assert_eq!("--verbose", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "deploy", "-V", "--verbose"]));
+        assert_eq!(
+            ParseError::DuplicateOption {
+                token: "--verbose".to_string(),
+                command: argv(&["deploy"])
+            },
+            res.unwrap_err()
+        );
+
+        let path = vec![&app.commands[0], &app.commands[0].subcommands[0]];
+        let help = crate::help::format_help_command(&app, &path, None);
+        assert!(help.contains("GLOBAL OPTIONS:\n"));
+        assert!(help.contains("-V --verbose"));
+
+        let help = crate::help::format_help_app(&app, None);
+        assert!(help.contains("GLOBAL OPTIONS:\n"));
+    }
+
+    #[test]
+    fn app_global_options_before_command() {
+        let deploy_cmd = Command::new("deploy", "Deploy the app", options_fn)
+            .option(CommandOption::new("force", "Skip checks"))
+            .subcommand(Command::new("status", "Show the deploy status", options_fn));
+
+        let app = create_app!()
+            .name("app_name")
+            .global_option(CommandOption::new("verbose", "Print more output").alias("V"))
+            .global_option(
+                CommandOption::new("dir", "Run in a folder")
+                    .alias("C")
+                    .arg("dir"),
+            )
+            .command(deploy_cmd);
+
+        let res = app.run_custom(argv(&[
+            "app_name",
+            "--verbose",
+            "-C",
+            "repo",
+            "deploy",
+            "status",
+        ]));
+        assert_eq!("--verbose --dir=repo", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "-VCrepo", "deploy", "--force"]));
+        assert_eq!("--verbose --dir=repo --force", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "--verbose", "deploy", "--verbose"]));
+        assert_eq!(
+            ParseError::DuplicateOption {
+                token: "--verbose".to_string(),
+                command: argv(&["deploy"])
+            },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "--verbose", "--help", "deploy"]));
+        assert_eq!(
+            ParseError::HelpRequested { command: None },
+            res.unwrap_err()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "-V"]));
+        assert_eq!(ParseError::MissingCommand, res.unwrap_err());
+
+        let res = app.try_run_custom(argv(&["app_name", "--force", "deploy"]));
+        assert!(matches!(
+            res.unwrap_err(),
+            ParseError::UnknownOption { command: None, .. }
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn app_global_option_version_alias() {
+        let _ = create_app!().global_option(CommandOption::new("verbose", "Print more").alias("v"));
+    }
+
+    #[test]
+    fn app_global_options_shadowed_before_command() {
+        let deploy_cmd = Command::new("deploy", "Deploy the app", options_fn)
+            .option(CommandOption::new("dry-run", "Only print the changes").alias("n"))
+            .option(CommandOption::new("verbatim", "Keep the output as is"));
+
+        let app = create_app!()
+            .name("app_name")
+            .infer_prefixes()
+            .global_option(CommandOption::new("verbose", "Print more output"))
+            .global_option(
+                CommandOption::new("namespace", "The namespace")
+                    .alias("n")
+                    .arg("namespace"),
+            )
+            .command(deploy_cmd);
+
+        // Options before the command only match the global options, even if
+        // a command option has the same alias or a prefix would be ambiguous
+        let res = app.run_custom(argv(&["app_name", "-n", "prod", "--verb", "deploy"]));
+        assert_eq!("--namespace=prod --verbose", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "-nprod", "deploy", "-n"]));
+        assert_eq!("--namespace=prod --dry-run", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "deploy", "--verb"]));
+        assert!(matches!(
+            res.unwrap_err(),
+            ParseError::AmbiguousOption { .. }
+        ));
+
+        let res = app.try_run_custom(argv(&["app_name", "--verbose=yes", "deploy"]));
+        assert_eq!(
+            ParseError::UnexpectedOptionArgument {
+                token: "--verbose".to_string(),
+                command: argv(&["deploy"])
+            },
+            res.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn option_relations_fallback_values() {
+        std::env::set_var("CLIMB_TEST_RELATIONS_TABLE", "true");
+        std::env::set_var("CLIMB_TEST_RELATIONS_ID", "1");
+        std::env::remove_var("CLIMB_TEST_RELATIONS_JSON");
+
+        let list_cmd = Command::new("list", "List the items", options_fn)
+            .option(CommandOption::new("json", "Print JSON").env("CLIMB_TEST_RELATIONS_JSON"))
+            .option(CommandOption::new("table", "Print a table").env("CLIMB_TEST_RELATIONS_TABLE"))
+            .option(CommandOption::new("key", "Private key file").arg("file"))
+            .option(
+                CommandOption::new("cert", "Certificate file")
+                    .arg("file")
+                    .default_value("c.pem"),
+            )
+            .option(
+                CommandOption::new("id", "The id of the item")
+                    .arg("id")
+                    .env("CLIMB_TEST_RELATIONS_ID"),
+            )
+            .option(CommandOption::new("name", "The name of the item").arg("name"))
+            .conflicting_options(&["json", "table"])
+            .option_requires("key", "cert")
+            .required_one_of(&["id", "name"]);
+
+        let app = create_app!().name("app_name").command(list_cmd);
+
+        // `--json` takes precedence over the value of `--table` from the
+        // environment, `--cert` is filled in from its default value, and
+        // `--id` from the environment
+        let res_conflict = app.try_run_custom(argv(&["app_name", "list", "--name", "x", "--json"]));
+        let res_requires =
+            app.try_run_custom(argv(&["app_name", "list", "--name", "x", "--key", "a.pem"]));
+        let res_one_of = app.try_run_custom(argv(&["app_name", "list", "--table"]));
+
+        // Options that are both filled in still conflict
+        std::env::set_var("CLIMB_TEST_RELATIONS_JSON", "true");
+        let res_both = app.try_run_custom(argv(&["app_name", "list"]));
+
+        std::env::remove_var("CLIMB_TEST_RELATIONS_TABLE");
+        std::env::remove_var("CLIMB_TEST_RELATIONS_ID");
+        std::env::remove_var("CLIMB_TEST_RELATIONS_JSON");
+
+        assert_eq!(
+            Ok(Ok(Some("--name=x --json --cert=c.pem --id=1".to_string()))),
+            res_conflict
+        );
+        assert_eq!(
+            Ok(Ok(Some(
+                "--name=x --key=a.pem --table --cert=c.pem --id=1".to_string()
+            ))),
+            res_requires
+        );
+        assert_eq!(
+            Ok(Ok(Some("--table --cert=c.pem --id=1".to_string()))),
+            res_one_of
+        );
+        assert_eq!(
+            ParseError::ConflictingOptions {
+                token: "--json".to_string(),
+                other: "--table".to_string(),
+                command: argv(&["list"])
+            },
+            res_both.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn option_relations() {
+        let list_cmd = Command::new("list", "List the items", options_fn)
+            .option(CommandOption::new("json", "Print JSON"))
+            .option(CommandOption::new("table", "Print a table"))
+            .option(CommandOption::new("key", "Private key file").arg("file"))
+            .option(CommandOption::new("cert", "Certificate file").arg("file"))
+            .option(CommandOption::new("id", "The id of the item").arg("id"))
+            .option(CommandOption::new("name", "The name of the item").arg("name"))
+            .conflicting_options(&["json", "table"])
+            .option_requires("key", "cert")
+            .required_one_of(&["id", "name"]);
+
+        let app = create_app!().name("app_name").command(list_cmd);
+
+        let list = |options: &[&str]| {
+            let mut args = argv(&["app_name", "list"]);
+            args.extend(options.iter().map(|option| option.to_string()));
+            app.try_run_custom(args)
+        };
+
+        assert_eq!(
+            Ok(Ok(Some("--name=x --json".to_string()))),
+            list(&["--name", "x", "--json"])
+        );
+        assert_eq!(
+            ParseError::ConflictingOptions {
+                token: "--json".to_string(),
+                other: "--table".to_string(),
+                command: argv(&["list"])
+            },
+            list(&["--id", "1", "--table", "--json"]).unwrap_err()
+        );
+        assert_eq!(
+            ParseError::MissingOptionDependency {
+                token: "--key".to_string(),
+                required: "--cert".to_string(),
+                command: argv(&["list"])
+            },
+            list(&["--id", "1", "--key", "a.pem"]).unwrap_err()
+        );
+        assert_eq!(
+            "One of these options must be provided: `--id` or `--name`",
+            list(&["--key", "a.pem", "--cert", "b.pem"])
+                .unwrap_err()
+                .to_string()
+        );
+
+        let path = vec![&app.commands[0]];
+        let help = crate::help::format_help_command(&app, &path, None);
+        assert!(help.contains("OPTION GROUPS:\n"));
+        assert!(help.contains("\t--key"));
+        assert!(help.contains("Requires --cert\n"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn option_relation_unknown_option() {
+        Command::new("list", "List the items", options_fn)
+            .option(CommandOption::new("json", "Print JSON"))
+            .conflicting_options(&["json", "table"]);
+    }
+
+    #[test]
+    fn choices() {
+        let log_cmd = Command::new("log", "Write a log message", options_fn)
+            .arg(CommandArg::new("env").choices(["dev", "production"]))
+            .option(
+                CommandOption::new("level", "The log level")
+                    .arg("level")
+                    .choices(["debug", "info", "warn"]),
+            );
+
+        let app = create_app!().name("app_name").command(log_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "log", "dev", "--level=warn"]));
+        assert_eq!("--level=warn", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "log", "dev", "--level", "trace"]));
+        assert_eq!(
+            "Invalid value `trace` for `--level`: expected one of debug, info, warn",
+            res.unwrap_err().to_string()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "log", "test"]));
+        assert_eq!(
+            ParseError::InvalidValue {
+                token: "test".to_string(),
+                command: argv(&["log"]),
+                argument: "<ENV>".to_string(),
+                message: "expected one of dev, production".to_string()
+            },
+            res.unwrap_err()
+        );
+
+        let path = vec![&app.commands[0]];
+        let help = crate::help::format_help_command(&app, &path, None);
+        assert!(help.contains("--level <LEVEL> [debug|info|warn]"));
+        assert!(help.contains("[dev|production]"));
+
+        let res = app.run_custom(argv(&[
+            "app_name",
+            "__complete",
+            "--",
+            "log",
+            "dev",
+            "--level",
+            "",
+        ]));
+        assert_eq!("debug\ninfo\nwarn", res.unwrap().unwrap());
+    }
+
+    #[test]
+    fn choices_arg_kinds_and_env() {
+        std::env::set_var("CLIMB_TEST_CHOICES_LEVEL", "trace");
+        std::env::set_var("CLIMB_TEST_CHOICES_FORMAT", "xml");
+
+        let log_cmd = Command::new("log", "Write a log message", join_fn)
+            .arg(
+                CommandArg::new("level")
+                    .optional("info")
+                    .choices(["info", "warn"]),
+            )
+            .arg(
+                CommandArg::new("targets")
+                    .variadic(0, None)
+                    .choices(["file", "stdout"]),
+            );
+        let show_cmd = Command::new("show", "Show the log", options_fn)
+            .arg(
+                CommandArg::new("level")
+                    .optional("info")
+                    .env("CLIMB_TEST_CHOICES_LEVEL")
+                    .choices(["info", "warn"]),
+            )
+            .option(
+                CommandOption::new("format", "Output format")
+                    .arg("format")
+                    .env("CLIMB_TEST_CHOICES_FORMAT")
+                    .choices(["json", "text"]),
+            );
+
+        let app = create_app!()
+            .name("app_name")
+            .command(log_cmd)
+            .command(show_cmd);
+
+        let res_optional = app.run_custom(argv(&["app_name", "log"]));
+        let res_variadic = app.run_custom(argv(&["app_name", "log", "warn", "file", "stdout"]));
+        let res_invalid = app.try_run_custom(argv(&["app_name", "log", "warn", "file", "disk"]));
+        let res_option_env = app.try_run_custom(argv(&["app_name", "show", "warn"]));
+        let res_arg_env = app.try_run_custom(argv(&["app_name", "show", "--format=json"]));
+
+        std::env::remove_var("CLIMB_TEST_CHOICES_LEVEL");
+        std::env::remove_var("CLIMB_TEST_CHOICES_FORMAT");
+
+        assert_eq!("info", res_optional.unwrap().unwrap());
+        assert_eq!("warn file stdout", res_variadic.unwrap().unwrap());
+        assert_eq!(
+            "Invalid value `disk` for <TARGETS>: expected one of file, stdout",
+            res_invalid.unwrap_err().to_string()
+        );
+
+        // Values from the environment name the variable they were read from
+        assert_eq!(
+            "Invalid value `xml` for `--format`: expected one of json, text \
+             (read from the `CLIMB_TEST_CHOICES_FORMAT` environment variable)",
+            res_option_env.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Invalid value `trace` for <LEVEL>: expected one of info, warn \
+             (read from the `CLIMB_TEST_CHOICES_LEVEL` environment variable)",
+            res_arg_env.unwrap_err().to_string()
+        );
+
+        let path = vec![&app.commands[0]];
+        let help = crate::help::format_help_command(&app, &path, None);
+        assert!(help.contains("[info|warn]"));
+        assert!(help.contains("[file|stdout]"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn choices_without_arg() {
+        CommandOption::new("level", "The log level").choices(["debug", "info"]);
+    }
+
+    #[test]
+    fn validators_and_transforms() {
+        fn input_fn(input: FunctionInput, options: FunctionOptions) -> FunctionResult {
+            Ok(Some(format!(
+                "{} {}",
+                input.join(" "),
+                options_fn(vec![], options)?.unwrap()
+            )))
+        }
+
+        let add_cmd = Command::new("add", "Add a user", input_fn)
+            .arg(
+                CommandArg::new("name")
+                    .transform(|value| value.trim().to_string())
+                    .transform(|value| value.to_lowercase())
+                    .validator(|value| match value.is_empty() {
+                        true => Err(String::from("the name is empty")),
+                        false => Ok(()),
+                    }),
+            )
+            .option(
+                CommandOption::new("age", "The age of the user")
+                    .arg_typed::<u32>("age")
+                    .transform(|value| value.trim_start_matches('0').to_string())
+                    .validator(|value| match value.parse::<u32>() {
+                        Ok(1..=150) => Ok(()),
+                        _ => Err(String::from("expected an age between 1 and 150")),
+                    }),
+            );
+
+        let app = create_app!().name("app_name").command(add_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "add", " Alice ", "--age=042"]));
+        assert_eq!("alice --age=42", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "add", "alice", "--age", "200"]));
+        assert_eq!(
+            "Invalid value `200` for `--age`: expected an age between 1 and 150",
+            res.unwrap_err().to_string()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "add", "  "]));
+        assert_eq!(
+            ParseError::InvalidValue {
+                token: "  ".to_string(),
+                command: argv(&["add"]),
+                argument: "<NAME>".to_string(),
+                message: "the name is empty".to_string()
+            },
+            res.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn validators_and_transforms_arg_kinds() {
+        let tag_cmd = Command::new("tag", "Tag some items", join_fn)
+            .arg(
+                CommandArg::new("count")
+                    .typed::<u8>()
+                    .validator(|value| match value {
+                        "0" => Err(String::from("expected at least one")),
+                        _ => Ok(()),
+                    }),
+            )
+            .arg(
+                CommandArg::new("color")
+                    .optional("RED")
+                    .transform(|value| value.to_lowercase()),
+            )
+            .arg(
+                CommandArg::new("tags")
+                    .variadic(0, None)
+                    .transform(|value| value.trim().to_string())
+                    .validator(|value| match value.is_empty() {
+                        true => Err(String::from("expected a tag")),
+                        false => Ok(()),
+                    }),
+            );
+
+        let app = create_app!().name("app_name").command(tag_cmd);
+
+        let res = app.run_custom(argv(&["app_name", "tag", "2"]));
+        assert_eq!("2 red", res.unwrap().unwrap());
+
+        let res = app.run_custom(argv(&["app_name", "tag", "2", "Blue", " a ", "b "]));
+        assert_eq!("2 blue a b", res.unwrap().unwrap());
+
+        let res = app.try_run_custom(argv(&["app_name", "tag", "0"]));
+        assert_eq!(
+            "Invalid value `0` for <COUNT>: expected at least one",
+            res.unwrap_err().to_string()
+        );
+
+        let res = app.try_run_custom(argv(&["app_name", "tag", "2", "red", "a", " "]));
+        assert_eq!(
+            "Invalid value ` ` for <TAGS>: expected a tag",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn validators_and_transforms_fallback_values() {
+        let greet_cmd = Command::new("greet", "Greet a user", join_fn)
+            .arg(
+                CommandArg::new("name")
+                    .env("CLIMB_TEST_VALIDATED_NAME")
+                    .transform(|value| value.to_lowercase())
+                    .validator(|value| match value.chars().all(char::is_alphabetic) {
+                        true => Ok(()),
+                        false => Err(String::from("expected only letters")),
+                    }),
+            )
+            .option(
+                CommandOption::new("times", "How many times to greet")
+                    .arg("times")
+                    .default_value("0")
+                    .validator(|value| match value {
+                        "0" => Err(String::from("expected at least one")),
+                        _ => Ok(()),
+                    }),
+            );
+
+        let app = create_app!().name("app_name").command(greet_cmd);
+
+        // Values from the environment are transformed and validated
+        std::env::set_var("CLIMB_TEST_VALIDATED_NAME", "BOB");
+        let res = app.run_custom(argv(&["app_name", "greet", "--times", "2"]));
+        assert_eq!("bob", res.unwrap().unwrap());
+
+        std::env::set_var("CLIMB_TEST_VALIDATED_NAME", "B0B");
+        let res = app.try_run_custom(argv(&["app_name", "greet", "--times", "2"]));
+        std::env::remove_var("CLIMB_TEST_VALIDATED_NAME");
+        assert_eq!(
+            "Invalid value `B0B` for <NAME>: expected only letters \
+             (read from the `CLIMB_TEST_VALIDATED_NAME` environment variable)",
+            res.unwrap_err().to_string()
+        );
+
+        // So are default values
+        let res = app.try_run_custom(argv(&["app_name", "greet", "alice"]));
+        assert_eq!(
+            "Invalid value `0` for `--times`: expected at least one",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn validator_without_arg() {
+        CommandOption::new("force", "Force the operation").validator(|_| Ok(()));
+    }
 }
@@ -0,0 +1,250 @@
+use crate::command::{Command, CommandArg, CommandOption};
+use crate::help::{
+    collect_command_paths, format_arg_str, format_choices_str, format_command_path,
+    format_command_usage_str,
+};
+use crate::App;
+
+// Renders the man page of the application: its usage, options, and every
+// command and subcommand along with their arguments and options
+pub(crate) fn render_app(app: &App) -> String {
+    let mut page = String::new();
+
+    write_header(&mut page, app, &app.name, &app.desc);
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(&format!(
+        "\\fB{}\\fR [\\fIOPTIONS\\fR] [\\fICOMMAND\\fR]\n",
+        escape(&app.name)
+    ));
+
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(&format!("{}\n", escape(&app.desc)));
+
+    page.push_str(".SH OPTIONS\n");
+    write_options(&mut page, &app.options);
+
+    if !app.global_options.is_empty() {
+        page.push_str(".SH \"GLOBAL OPTIONS\"\n");
+        write_options(&mut page, &app.global_options);
+    }
+
+    let command_paths = collect_command_paths(app);
+    if !command_paths.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+    }
+
+    for command_path in &command_paths {
+        let command = *command_path.last().unwrap();
+
+        page.push_str(&format!(
+            ".SS {}\n",
+            quote(&format_command_path(command_path))
+        ));
+        page.push_str(&format!("{}\n", escape(&command.desc)));
+        page.push_str(".PP\n");
+        page.push_str(&format!(
+            "Usage: {}\n",
+            escape(format_command_usage_str(app, command_path).trim_start())
+        ));
+
+        if let Some(alias_short) = &command.alias_short {
+            page.push_str(".PP\n");
+            page.push_str(&format!("Alias: \\fB{}\\fR\n", escape(alias_short)));
+        }
+
+        if !command.args.is_empty() {
+            page.push_str(".PP\n\\fBArguments:\\fR\n");
+            write_args(&mut page, &command.args);
+        }
+
+        if !command.options.is_empty() {
+            page.push_str(".PP\n\\fBOptions:\\fR\n");
+            write_options(&mut page, &command.options);
+        }
+    }
+
+    page
+}
+
+// Renders a man page for a single command. The page is named after the
+// application and the long aliases of the command path, e.g. `app-remote-add`
+pub(crate) fn render_command(app: &App, command_path: &[&Command]) -> String {
+    let command = *command_path.last().unwrap();
+    let mut page = String::new();
+
+    write_header(&mut page, app, &page_name(app, command_path), &command.desc);
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(&format!(
+        "{}\n",
+        escape(format_command_usage_str(app, command_path).trim_start())
+    ));
+
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(&format!("{}\n", escape(&command.desc)));
+
+    if let Some(alias_short) = &command.alias_short {
+        page.push_str(".PP\n");
+        page.push_str(&format!("Alias: \\fB{}\\fR\n", escape(alias_short)));
+    }
+
+    if !command.args.is_empty() {
+        page.push_str(".SH ARGUMENTS\n");
+        write_args(&mut page, &command.args);
+    }
+
+    if !command.options.is_empty() {
+        page.push_str(".SH OPTIONS\n");
+        write_options(&mut page, &command.options);
+    }
+
+    if !app.global_options.is_empty() {
+        page.push_str(".SH \"GLOBAL OPTIONS\"\n");
+        write_options(&mut page, &app.global_options);
+    }
+
+    if !command.subcommands.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+        for subcommand in &command.subcommands {
+            page.push_str(".TP\n");
+            page.push_str(&format!("{}\n", format_command_aliases(subcommand)));
+            page.push_str(&format!("{}\n", escape(&subcommand.desc)));
+        }
+    }
+
+    page.push_str(".SH \"SEE ALSO\"\n");
+    page.push_str(&format!("\\fB{}\\fR(1)\n", escape(&app.name)));
+
+    page
+}
+
+// Returns the name of the man page of a command, e.g. `app-remote-add`
+pub(crate) fn page_name(app: &App, command_path: &[&Command]) -> String {
+    let mut name = app.name.clone();
+
+    for command in command_path {
+        name.push('-');
+        name.push_str(&command.alias_long);
+    }
+
+    name
+}
+
+// Writes the title line and the NAME section
+fn write_header(page: &mut String, app: &App, name: &str, desc: &str) {
+    page.push_str(&format!(
+        ".TH {} 1 \"\" {} \"User Commands\"\n",
+        quote(&name.to_uppercase()),
+        quote(format!("{} {}", app.name, app.version).trim_end())
+    ));
+
+    page.push_str(".SH NAME\n");
+    page.push_str(&format!("{} \\- {}\n", escape(name), escape(desc)));
+}
+
+fn write_args(page: &mut String, args: &[CommandArg]) {
+    for arg in args {
+        page.push_str(".TP\n");
+        page.push_str(&format!("\\fI{}\\fR", escape(&format_arg_str(arg))));
+
+        if !arg.choices.is_empty() {
+            page.push_str(&format!(" {}", escape(&format_choices_str(&arg.choices))));
+        }
+
+        let details = format_details(&arg.default, &arg.env);
+        if !details.is_empty() {
+            page.push_str(&format!("\n{}", escape(&details)));
+        }
+
+        page.push('\n');
+    }
+}
+
+fn write_options(page: &mut String, options: &[CommandOption]) {
+    for option in options {
+        page.push_str(".TP\n");
+
+        let mut aliases = Vec::<String>::new();
+        if let Some(alias_short) = &option.alias_short {
+            aliases.push(format!("\\fB{}\\fR", escape(alias_short)));
+        }
+        aliases.push(format!("\\fB{}\\fR", escape(&option.alias_long)));
+        page.push_str(&aliases.join(", "));
+
+        if let Some(argument_name) = &option.arg {
+            page.push_str(&format!(" \\fI<{}>\\fR", escape(argument_name)));
+        }
+
+        // Repeatable options are followed by an ellipsis
+        if option.repeatable {
+            page.push_str("...");
+        }
+
+        if !option.choices.is_empty() {
+            page.push_str(&format!(
+                " {}",
+                escape(&format_choices_str(&option.choices))
+            ));
+        }
+
+        page.push_str(&format!("\n{}", escape(&option.description)));
+
+        let details = format_details(&option.default, &option.env);
+        if !details.is_empty() {
+            page.push_str(&format!(" {}", escape(&details)));
+        }
+
+        page.push('\n');
+    }
+}
+
+// Formats the default value and environment variable of an argument or
+// option, if it has any
+fn format_details(default: &Option<String>, env: &Option<String>) -> String {
+    let mut details = Vec::<String>::new();
+
+    if let Some(default) = default {
+        details.push(format!("[default: {}]", default));
+    }
+
+    if let Some(var) = env {
+        details.push(format!("[env: {}]", var));
+    }
+
+    details.join(" ")
+}
+
+fn format_command_aliases(command: &Command) -> String {
+    let mut aliases = vec![format!("\\fB{}\\fR", escape(&command.alias_long))];
+
+    if let Some(alias_short) = &command.alias_short {
+        aliases.push(format!("\\fB{}\\fR", escape(alias_short)));
+    }
+
+    aliases.join(", ")
+}
+
+// Escapes text so that roff prints it as is. Lines that start with a dot or
+// an apostrophe would otherwise be read as requests
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+
+    escaped
+        .lines()
+        .map(|line| {
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Escapes text and wraps it in double quotes so that it is read as a single
+// argument of a request
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text).replace('"', "\\(dq"))
+}
@@ -0,0 +1,175 @@
+use crate::command::{Command, CommandArg, CommandOption};
+use crate::help::{
+    collect_command_paths, format_arg_str, format_choices_str, format_command_path,
+    format_command_usage_str,
+};
+use crate::App;
+
+// Renders the reference documentation of the application: its usage and
+// options, followed by a section for every command and subcommand
+pub(crate) fn render(app: &App) -> String {
+    let mut doc = format!("# {}\n\n", app.name);
+
+    if !app.desc.is_empty() {
+        doc.push_str(&format!("{}\n\n", app.desc));
+    }
+
+    if !app.version.is_empty() {
+        doc.push_str(&format!("Version: `{}`\n\n", app.version));
+    }
+
+    doc.push_str("## Usage\n\n");
+    write_code_block(&mut doc, &format!("{} [OPTIONS] [COMMAND]", app.name));
+
+    doc.push_str("## Options\n\n");
+    write_options(&mut doc, &app.options);
+
+    // Global options are accepted by every command, so they are only listed
+    // once for the whole application
+    if !app.global_options.is_empty() {
+        doc.push_str("## Global options\n\n");
+        write_options(&mut doc, &app.global_options);
+    }
+
+    if !app.commands.is_empty() {
+        doc.push_str("## Commands\n\n");
+        write_commands(&mut doc, &app.commands);
+    }
+
+    for command_path in collect_command_paths(app) {
+        write_command(&mut doc, app, &command_path);
+    }
+
+    // Only keep a single newline at the end of the document
+    doc.truncate(doc.trim_end().len());
+    doc.push('\n');
+    doc
+}
+
+fn write_command(doc: &mut String, app: &App, command_path: &[&Command]) {
+    let command = *command_path.last().unwrap();
+
+    doc.push_str(&format!(
+        "## {} {}\n\n",
+        app.name,
+        format_command_path(command_path)
+    ));
+    doc.push_str(&format!("{}\n\n", command.desc));
+
+    if let Some(alias_short) = &command.alias_short {
+        doc.push_str(&format!("Alias: `{}`\n\n", alias_short));
+    }
+
+    doc.push_str("### Usage\n\n");
+    write_code_block(
+        doc,
+        format_command_usage_str(app, command_path).trim_start(),
+    );
+
+    if !command.args.is_empty() {
+        doc.push_str("### Arguments\n\n");
+        write_args(doc, &command.args);
+    }
+
+    if !command.options.is_empty() {
+        doc.push_str("### Options\n\n");
+        write_options(doc, &command.options);
+    }
+
+    if !command.subcommands.is_empty() {
+        doc.push_str("### Commands\n\n");
+        write_commands(doc, &command.subcommands);
+    }
+}
+
+fn write_code_block(doc: &mut String, code: &str) {
+    doc.push_str(&format!("```text\n{}\n```\n\n", code));
+}
+
+fn write_args(doc: &mut String, args: &[CommandArg]) {
+    doc.push_str("| Argument | Default | Environment |\n");
+    doc.push_str("| --- | --- | --- |\n");
+
+    for arg in args {
+        let mut arg_str = format_arg_str(arg);
+        if !arg.choices.is_empty() {
+            arg_str.push_str(&format!(" {}", format_choices_str(&arg.choices)));
+        }
+
+        doc.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            escape_cell(&arg_str),
+            format_code_cell(&arg.default),
+            format_code_cell(&arg.env)
+        ));
+    }
+
+    doc.push('\n');
+}
+
+fn write_options(doc: &mut String, options: &[CommandOption]) {
+    doc.push_str("| Option | Description | Default | Environment |\n");
+    doc.push_str("| --- | --- | --- | --- |\n");
+
+    for option in options {
+        let mut aliases = Vec::<String>::new();
+        if let Some(alias_short) = &option.alias_short {
+            aliases.push(format!("`{}`", alias_short));
+        }
+
+        let mut alias_long = option.alias_long.clone();
+        if let Some(argument_name) = &option.arg {
+            alias_long.push_str(&format!(" <{}>", argument_name));
+        }
+
+        // Repeatable options are followed by an ellipsis
+        if option.repeatable {
+            alias_long.push_str("...");
+        }
+
+        if !option.choices.is_empty() {
+            alias_long.push_str(&format!(" {}", format_choices_str(&option.choices)));
+        }
+
+        aliases.push(format!("`{}`", escape_cell(&alias_long)));
+
+        doc.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            aliases.join(", "),
+            escape_cell(&option.description),
+            format_code_cell(&option.default),
+            format_code_cell(&option.env)
+        ));
+    }
+
+    doc.push('\n');
+}
+
+fn write_commands(doc: &mut String, commands: &[Command]) {
+    doc.push_str("| Command | Alias | Description |\n");
+    doc.push_str("| --- | --- | --- |\n");
+
+    for command in commands {
+        doc.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            command.alias_long,
+            format_code_cell(&command.alias_short),
+            escape_cell(&command.desc)
+        ));
+    }
+
+    doc.push('\n');
+}
+
+// Formats an optional value as inline code, or leaves the cell empty
+fn format_code_cell(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("`{}`", escape_cell(value)),
+        None => String::new(),
+    }
+}
+
+// Escapes text so that it stays inside a single table cell
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
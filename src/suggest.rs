@@ -0,0 +1,63 @@
+// Returns the candidates that are closest to a mistyped command or option,
+// at most three of them. Leading dashes are ignored, so `--h` suggests `-h`.
+// Candidates are only suggested if they are similar enough to the token
+pub(crate) fn suggest<'a>(
+    token: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let token_chars: Vec<char> = token.trim_start_matches('-').chars().collect();
+
+    let mut scored = Vec::<(usize, &str)>::new();
+    for candidate in candidates {
+        let candidate_chars: Vec<char> = candidate.trim_start_matches('-').chars().collect();
+        let distance = edit_distance(&token_chars, &candidate_chars);
+
+        // Allow one edit for every three characters, and never suggest a
+        // candidate that would have to be replaced entirely
+        let max_distance = (token_chars.len().max(candidate_chars.len()) / 3).max(1);
+        if distance <= max_distance && distance < candidate_chars.len() {
+            scored.push((distance, candidate));
+        }
+    }
+
+    let Some(closest) = scored.iter().map(|(distance, _)| *distance).min() else {
+        return vec![];
+    };
+
+    let mut suggestions = Vec::<String>::new();
+    for (_, candidate) in scored.iter().filter(|(distance, _)| *distance == closest) {
+        if !suggestions.iter().any(|suggestion| suggestion == candidate) {
+            suggestions.push(candidate.to_string());
+        }
+    }
+
+    suggestions.truncate(3);
+    suggestions
+}
+
+// Counts the insertions, deletions, substitutions, and swaps of adjacent
+// characters needed to turn one string into the other
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            rows[i][j] = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
@@ -1,3 +1,7 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
 /// Renaming of type `Vec<String>`
 ///
 /// Stores the arguments that are passed into a function.
@@ -22,15 +26,16 @@ pub type FunctionOptions = Vec<FunctionOption>;
 /// This type is returned from all Climb commands.
 pub type FunctionResult = Result<Option<String>, String>;
 
-/// Renaming of type `fn(FunctionInput, FunctionOptions) -> FunctionResult`.
+/// Renaming of type `Box<dyn Fn(FunctionInput, FunctionOptions) -> FunctionResult>`.
 ///
 /// This is the general signature for all climb functions. If you
-/// create a function with this signature, you can then pass it
-/// into a command to be executed.
+/// create a function or closure with this signature, you can then pass it
+/// into a command to be executed. Closures can capture state such as
+/// configuration or database connections.
 ///
 /// See [FunctionInput], [FunctionOptions], [FunctionResult] for more
 /// details.
-pub type CommandFunction = fn(FunctionInput, FunctionOptions) -> FunctionResult;
+pub type CommandFunction = Box<dyn Fn(FunctionInput, FunctionOptions) -> FunctionResult>;
 
 /// Stores the options that are passed into functions
 ///
@@ -38,3 +43,165 @@ pub type CommandFunction = fn(FunctionInput, FunctionOptions) -> FunctionResult;
 /// the second variable as Some(input), otherwise it is None
 #[derive(PartialEq, Eq)]
 pub struct FunctionOption(pub String, pub Option<String>);
+
+/// Parses a value passed in from the command line into a typed value.
+///
+/// Value parsers are attached to command and option arguments. Climb runs
+/// them while parsing the command line, so a value that can't be parsed is
+/// reported as a normal parsing error before your command function is called.
+///
+/// Any type that implements [FromStr] can be parsed using [FromStrParser],
+/// which is what [Command::arg_typed](crate::Command::arg_typed) and
+/// [CommandOption::arg_typed](crate::CommandOption::arg_typed) use. Implement
+/// this trait to parse your own types.
+///
+/// # Examples
+///
+/// Creating a parser that only accepts even numbers:
+///
+/// ```
+/// use climb::*;
+///
+/// struct EvenParser;
+///
+/// impl ValueParser for EvenParser {
+///     type Value = u32;
+///
+///     fn parse(&self, value: &str) -> Result<u32, String> {
+///         match value.parse::<u32>() {
+///             Ok(num) if num % 2 == 0 => Ok(num),
+///             _ => Err(String::from("expected an even number")),
+///         }
+///     }
+/// }
+///
+/// let my_command = Command::new(
+///     "cmd_name",
+///     "cmd_desc",
+///     |input: FunctionInput, _: FunctionOptions| {
+///         let num = input.value_with(0, &EvenParser).unwrap();
+///         Ok(Some(num.to_string()))
+///     }
+/// )
+/// .arg_parser("number", EvenParser);
+/// ```
+pub trait ValueParser {
+    /// The type of the parsed value
+    type Value;
+
+    /// Parse the given value, returning an error message if it is invalid
+    fn parse(&self, value: &str) -> Result<Self::Value, String>;
+}
+
+/// [ValueParser] for any type that implements [FromStr]
+pub struct FromStrParser<T>(PhantomData<T>);
+
+impl<T> FromStrParser<T> {
+    /// Construct and return a parser for the type `T`
+    pub fn new() -> Self {
+        FromStrParser(PhantomData)
+    }
+}
+
+impl<T> Default for FromStrParser<T> {
+    fn default() -> Self {
+        FromStrParser::new()
+    }
+}
+
+impl<T> ValueParser for FromStrParser<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    type Value = T;
+
+    fn parse(&self, value: &str) -> Result<T, String> {
+        value.parse::<T>().map_err(|e| e.to_string())
+    }
+}
+
+/// Typed getters for the arguments passed into a function.
+///
+/// Implemented for [FunctionInput]. Arguments declared with
+/// [Command::arg_typed](crate::Command::arg_typed) or
+/// [Command::arg_parser](crate::Command::arg_parser) are checked when the
+/// command line is parsed, so getting them as the same type will not fail.
+pub trait InputValues {
+    /// Get the argument at the given index parsed as the type `T`
+    ///
+    /// Returns `None` if there is no argument at the index or if it
+    /// can't be parsed.
+    fn value<T: FromStr>(&self, index: usize) -> Option<T>;
+
+    /// Get the argument at the given index parsed with a [ValueParser]
+    ///
+    /// Returns `None` if there is no argument at the index or if it
+    /// can't be parsed.
+    fn value_with<P: ValueParser>(&self, index: usize, parser: &P) -> Option<P::Value>;
+}
+
+impl InputValues for FunctionInput {
+    fn value<T: FromStr>(&self, index: usize) -> Option<T> {
+        self.get(index)?.parse().ok()
+    }
+
+    fn value_with<P: ValueParser>(&self, index: usize, parser: &P) -> Option<P::Value> {
+        parser.parse(self.get(index)?).ok()
+    }
+}
+
+/// Typed getters for the options passed into a function.
+///
+/// Implemented for [FunctionOptions]. Options are looked up by their long
+/// alias, including the leading dashes (e.g. `--count`). If a
+/// [repeatable](crate::CommandOption::repeatable) option was passed in more
+/// than once, the single value getters return its first value. Option arguments
+/// declared with [CommandOption::arg_typed](crate::CommandOption::arg_typed) or
+/// [CommandOption::arg_parser](crate::CommandOption::arg_parser) are checked
+/// when the command line is parsed, so getting them as the same type will
+/// not fail.
+pub trait OptionValues {
+    /// Get the argument of the given option parsed as the type `T`
+    ///
+    /// Returns `None` if the option wasn't passed in, it has no argument,
+    /// or the argument can't be parsed.
+    fn value<T: FromStr>(&self, alias: &str) -> Option<T>;
+
+    /// Get the argument of the given option parsed with a [ValueParser]
+    ///
+    /// Returns `None` if the option wasn't passed in, it has no argument,
+    /// or the argument can't be parsed.
+    fn value_with<P: ValueParser>(&self, alias: &str, parser: &P) -> Option<P::Value>;
+
+    /// Get the arguments of every occurrence of the given option parsed as the type `T`
+    ///
+    /// Arguments that can't be parsed are left out.
+    fn values<T: FromStr>(&self, alias: &str) -> Vec<T>;
+
+    /// Get the amount of times the given option was passed in
+    fn count(&self, alias: &str) -> usize;
+}
+
+impl OptionValues for FunctionOptions {
+    fn value<T: FromStr>(&self, alias: &str) -> Option<T> {
+        let option = self.iter().find(|option| option.0 == alias)?;
+        option.1.as_ref()?.parse().ok()
+    }
+
+    fn value_with<P: ValueParser>(&self, alias: &str, parser: &P) -> Option<P::Value> {
+        let option = self.iter().find(|option| option.0 == alias)?;
+        parser.parse(option.1.as_ref()?).ok()
+    }
+
+    fn values<T: FromStr>(&self, alias: &str) -> Vec<T> {
+        self.iter()
+            .filter(|option| option.0 == alias)
+            .filter_map(|option| option.1.as_ref()?.parse().ok())
+            .collect()
+    }
+
+    fn count(&self, alias: &str) -> usize {
+        self.iter().filter(|option| option.0 == alias).count()
+    }
+}